@@ -0,0 +1,40 @@
+//! Compares `JsonSchema::is_valid` against `JsonSchema::validate` on a value
+//! that fails a large `enum`, which `validate` has to clone into its error.
+//!
+//! Run with `cargo bench`.
+
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+use json_schema::Context;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use url::Url;
+
+const ITERATIONS: u32 = 1000;
+
+fn time<F: FnMut() -> bool>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        assert!(!f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let statuses = (0..10000).map(|i| Value::String(format!("status-{}", i))).collect::<Vec<_>>();
+    let mut ctx = Context::default();
+    let base_uri = Url::parse("http://example.com/schema.json")
+        .expect("Couldn't parse base URI");
+    let schema = ctx.make_schema(base_uri, &json!({
+        "properties": {"status": {"enum": statuses}}
+    })).expect("Invalid schema");
+    let value = json!({"status": "unknown"});
+
+    let validate = time(|| schema.validate(&value).is_ok());
+    let is_valid = time(|| schema.is_valid(&value));
+    println!("validate().is_ok(): {:?} per iteration", validate);
+    println!("is_valid():         {:?} per iteration", is_valid);
+}
@@ -0,0 +1,72 @@
+//! Compares validating against a 50-branch `oneOf` with and without a
+//! `discriminator`, on a value that matches the last branch.
+//!
+//! Run with `cargo bench`.
+
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+use json_schema::Context;
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+use url::Url;
+
+const BRANCHES: usize = 50;
+const ITERATIONS: u32 = 1000;
+
+fn time<F: FnMut() -> bool>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        assert!(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let mut definitions = Map::new();
+    let mut mapping = Map::new();
+    for i in 0..BRANCHES {
+        let kind = format!("kind-{}", i);
+        definitions.insert(kind.clone(), json!({
+            "type": "object",
+            "required": ["kind", "data"],
+            "properties": {
+                "kind": {"const": kind},
+                "data": {"type": "array", "items": {"type": "integer", "minimum": 0}}
+            }
+        }));
+        mapping.insert(kind.clone(), Value::String(format!("#/definitions/{}", kind)));
+    }
+    let branches = (0..BRANCHES)
+        .map(|i| json!({"$ref": format!("#/definitions/kind-{}", i)}))
+        .collect::<Vec<_>>();
+
+    let mut plain_ctx = Context::default();
+    let plain_uri = Url::parse("http://example.com/plain.json")
+        .expect("Couldn't parse URI");
+    let plain = plain_ctx.make_schema(plain_uri, &json!({
+        "definitions": definitions,
+        "oneOf": branches
+    })).expect("Invalid schema");
+
+    let mut discriminated_ctx = Context::default();
+    let discriminated_uri = Url::parse("http://example.com/discriminated.json")
+        .expect("Couldn't parse URI");
+    let discriminated = discriminated_ctx.make_schema(discriminated_uri, &json!({
+        "definitions": definitions,
+        "oneOf": branches,
+        "discriminator": {"propertyName": "kind", "mapping": mapping}
+    })).expect("Invalid schema");
+
+    let value = json!({
+        "kind": format!("kind-{}", BRANCHES - 1),
+        "data": (0..100).collect::<Vec<_>>()
+    });
+
+    let without = time(|| plain.validate(&value).is_ok());
+    let with = time(|| discriminated.validate(&value).is_ok());
+    println!("without discriminator: {:?} per iteration", without);
+    println!("with discriminator:    {:?} per iteration", with);
+}
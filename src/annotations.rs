@@ -0,0 +1,44 @@
+use json_pointer::JsonPointer;
+use url::Url;
+
+/// The annotations collected while validating a value. Created by
+/// [`JsonSchema::validate_annotated`](struct.JsonSchema.html#method.validate_annotated).
+///
+/// Paths are JSON pointers to the value the annotation applies to, as
+/// strings, and each list is in document order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Annotations {
+    any_of_matches: Vec<(String, Url)>,
+    formats: Vec<(String, String)>,
+}
+
+impl Annotations {
+    /// Returns the subschemas of each `anyOf` that the value at a path
+    /// matched. Every matching subschema is listed, not just the first.
+    pub fn any_of_matches(&self) -> &[(String, Url)] {
+        &self.any_of_matches
+    }
+
+    /// Returns the `format` keywords that applied to the value at a path.
+    /// Formats aren't checked, so these are only the names that were given.
+    pub fn formats(&self) -> &[(String, String)] {
+        &self.formats
+    }
+
+    /// Returns whether no annotations were collected.
+    pub fn is_empty(&self) -> bool {
+        self.any_of_matches.is_empty() && self.formats.is_empty()
+    }
+
+    pub(crate) fn push_any_of_match(&mut self, path: &[String], url: &Url) {
+        self.any_of_matches.push((pointer(path), url.clone()));
+    }
+
+    pub(crate) fn push_format(&mut self, path: &[String], format: &str) {
+        self.formats.push((pointer(path), format.to_string()));
+    }
+}
+
+fn pointer(path: &[String]) -> String {
+    JsonPointer::new(path.to_vec()).to_string()
+}
@@ -0,0 +1,171 @@
+use serde_json::{Map, Number, Value};
+
+/// A builder for constructing schemas in Rust rather than writing out JSON by
+/// hand.
+///
+/// The result is a plain JSON value, which can then be passed to
+/// [`Context::make_schema`](struct.Context.html#method.make_schema).
+///
+/// ```
+/// # extern crate json_schema;
+/// # extern crate url;
+/// # use json_schema::{Context, SchemaBuilder};
+/// # use url::Url;
+/// # fn main() {
+/// let schema = SchemaBuilder::object()
+///     .require("name")
+///     .property("name", SchemaBuilder::string().min_length(1))
+///     .property("age", SchemaBuilder::integer().minimum(0))
+///     .build();
+///
+/// let mut ctx = Context::default();
+/// let uri = Url::parse("http://example.com/person.json").unwrap();
+/// assert!(ctx.make_schema(uri, &schema).is_ok());
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaBuilder {
+    map: Map<String, Value>,
+}
+
+impl SchemaBuilder {
+    /// Creates a builder for a schema that matches every value.
+    pub fn new() -> SchemaBuilder {
+        SchemaBuilder::default()
+    }
+
+    /// Creates a builder for a schema that matches integers.
+    pub fn integer() -> SchemaBuilder {
+        SchemaBuilder::new().keyword("type", "integer")
+    }
+
+    /// Creates a builder for a schema that matches numbers.
+    pub fn number() -> SchemaBuilder {
+        SchemaBuilder::new().keyword("type", "number")
+    }
+
+    /// Creates a builder for a schema that matches objects.
+    pub fn object() -> SchemaBuilder {
+        SchemaBuilder::new().keyword("type", "object")
+    }
+
+    /// Creates a builder for a schema that matches strings.
+    pub fn string() -> SchemaBuilder {
+        SchemaBuilder::new().keyword("type", "string")
+    }
+
+    /// Creates a builder for a schema that matches values which match every
+    /// one of the given schemas.
+    pub fn all_of<I: IntoIterator<Item=T>, T: Into<Value>>(schemas: I) -> SchemaBuilder {
+        SchemaBuilder::new().combinator("allOf", schemas)
+    }
+
+    /// Creates a builder for a schema that matches values which match at
+    /// least one of the given schemas.
+    pub fn any_of<I: IntoIterator<Item=T>, T: Into<Value>>(schemas: I) -> SchemaBuilder {
+        SchemaBuilder::new().combinator("anyOf", schemas)
+    }
+
+    /// Combines this schema with another one, producing a schema that matches
+    /// values which match both.
+    pub fn and<T: Into<Value>>(self, other: T) -> SchemaBuilder {
+        SchemaBuilder::all_of(vec![self.build(), other.into()])
+    }
+
+    /// Combines this schema with another one, producing a schema that matches
+    /// values which match either.
+    pub fn or<T: Into<Value>>(self, other: T) -> SchemaBuilder {
+        SchemaBuilder::any_of(vec![self.build(), other.into()])
+    }
+
+    /// Sets the `title` of the schema.
+    pub fn title<S: Into<String>>(self, title: S) -> SchemaBuilder {
+        self.keyword("title", title.into())
+    }
+
+    /// Sets the `description` of the schema.
+    pub fn description<S: Into<String>>(self, description: S) -> SchemaBuilder {
+        self.keyword("description", description.into())
+    }
+
+    /// Sets the `maximum` of the schema.
+    pub fn maximum<N: Into<Number>>(self, n: N) -> SchemaBuilder {
+        self.keyword("maximum", Value::Number(n.into()))
+    }
+
+    /// Sets the `exclusiveMaximum` of the schema.
+    pub fn exclusive_maximum<N: Into<Number>>(self, n: N) -> SchemaBuilder {
+        self.keyword("exclusiveMaximum", Value::Number(n.into()))
+    }
+
+    /// Sets the `minimum` of the schema.
+    pub fn minimum<N: Into<Number>>(self, n: N) -> SchemaBuilder {
+        self.keyword("minimum", Value::Number(n.into()))
+    }
+
+    /// Sets the `exclusiveMinimum` of the schema.
+    pub fn exclusive_minimum<N: Into<Number>>(self, n: N) -> SchemaBuilder {
+        self.keyword("exclusiveMinimum", Value::Number(n.into()))
+    }
+
+    /// Sets the `maxLength` of the schema.
+    pub fn max_length(self, n: u64) -> SchemaBuilder {
+        self.keyword("maxLength", n)
+    }
+
+    /// Sets the `minLength` of the schema.
+    pub fn min_length(self, n: u64) -> SchemaBuilder {
+        self.keyword("minLength", n)
+    }
+
+    /// Sets the `pattern` of the schema.
+    pub fn pattern<S: Into<String>>(self, pattern: S) -> SchemaBuilder {
+        self.keyword("pattern", pattern.into())
+    }
+
+    /// Adds a schema for the property with the given name.
+    pub fn property<S: Into<String>, T: Into<Value>>(mut self, name: S, schema: T) -> SchemaBuilder {
+        self.map.entry("properties")
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .expect("properties wasn't an object")
+            .insert(name.into(), schema.into());
+        self
+    }
+
+    /// Sets the schema that properties not named by `property` must match.
+    pub fn additional_properties<T: Into<Value>>(self, schema: T) -> SchemaBuilder {
+        self.keyword("additionalProperties", schema.into())
+    }
+
+    /// Requires that the property with the given name be present.
+    pub fn require<S: Into<String>>(mut self, name: S) -> SchemaBuilder {
+        self.map.entry("required")
+            .or_insert_with(|| Value::Array(Vec::new()))
+            .as_array_mut()
+            .expect("required wasn't an array")
+            .push(Value::String(name.into()));
+        self
+    }
+
+    /// Returns the JSON value of the schema.
+    pub fn build(self) -> Value {
+        Value::Object(self.map)
+    }
+
+    fn combinator<I: IntoIterator<Item=T>, T: Into<Value>>(self, keyword: &str, schemas: I) -> SchemaBuilder {
+        let schemas = schemas.into_iter().map(Into::into).collect::<Vec<Value>>();
+        self.keyword(keyword, schemas)
+    }
+
+    fn keyword<T: Into<Value>>(mut self, keyword: &str, value: T) -> SchemaBuilder {
+        self.map.insert(keyword.to_string(), value.into());
+        self
+    }
+}
+
+impl From<SchemaBuilder> for Value {
+    fn from(builder: SchemaBuilder) -> Value {
+        builder.build()
+    }
+}
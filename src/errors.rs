@@ -1,5 +1,8 @@
-use schema::Condition;
-use serde_json::Value;
+use schema::{Condition, Type};
+use serde_json::{Number, Value};
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+use std::sync::Arc;
 use url::Url;
 use url::ParseError as UrlParseError;
 
@@ -50,6 +53,12 @@ pub enum FromValueError {
     /// RFC](tools.ietf.org/html/draft-wright-json-schema-01#section-7).
     SubschemaUsesSchemaKeyword(Value),
 
+    /// A value used a keyword that isn't defined by the specification, and
+    /// the [`Context`](struct.Context.html) is set to reject those.
+    ///
+    /// The second value is the unknown keyword.
+    UnknownKeyword(Value, String),
+
     /// An unknown value was specified for `$schema`.
     ///
     /// This crate only supports the draft06 `$schema` value, so this may occur
@@ -59,25 +68,475 @@ pub enum FromValueError {
     /// a supported version.
     UnknownSchemaVersion(Value, String),
 
-    /// An attempt was made to define a schema whose URI already exists.
+    /// An attempt was made to define a schema whose URI already exists, such
+    /// as a plain-name anchor (e.g. `"$id": "#foo"`) that's already used by
+    /// another schema in the same document.
     ///
     /// Illegal per [Section 9.2.2 of the Core
     /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-01#section-9.2.2).
     URIConflict(Value, Url),
 }
 
+impl Display for FromValueError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match *self {
+            FromValueError::BadPattern(_, ref err) => write!(fmt, "invalid pattern: {}", err),
+            FromValueError::InvalidId(_, ref id, ref err) => write!(fmt, "invalid $id {:?}: {}", id, err),
+            FromValueError::InvalidKeywordType(_, ref keyword, ref value) => {
+                write!(fmt, "{} has the wrong type: {}", keyword, value)
+            },
+            FromValueError::InvalidKeywordValue(_, ref keyword, ref value) => {
+                match unknown_type(keyword, value) {
+                    Some(ty) => {
+                        write!(fmt, "unknown type '{}', expected one of: ", ty)?;
+                        for (i, ty) in Type::ALL.iter().enumerate() {
+                            if i > 0 {
+                                write!(fmt, ", ")?;
+                            }
+                            write!(fmt, "{}", ty)?;
+                        }
+                        Ok(())
+                    },
+                    None => write!(fmt, "invalid value for {}: {}", keyword, value),
+                }
+            },
+            FromValueError::InvalidSchemaType(ref value) => {
+                write!(fmt, "a schema must be an object or a boolean, not {}", value)
+            },
+            FromValueError::MetaschemaFailedToValidate(ref err) => {
+                write!(fmt, "schema doesn't match the metaschema: {}", err)
+            },
+            FromValueError::SubschemaUsesSchemaKeyword(_) => {
+                write!(fmt, "$schema can only be used at the top level")
+            },
+            FromValueError::UnknownKeyword(_, ref keyword) => write!(fmt, "unknown keyword {:?}", keyword),
+            FromValueError::UnknownSchemaVersion(_, ref version) => {
+                write!(fmt, "unsupported $schema {:?}", version)
+            },
+            FromValueError::URIConflict(_, ref uri) => write!(fmt, "{} is already in use", uri),
+        }
+    }
+}
+
+impl StdError for FromValueError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            FromValueError::BadPattern(_, ref err) => Some(err),
+            FromValueError::InvalidId(_, _, ref err) => Some(err),
+            FromValueError::MetaschemaFailedToValidate(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the first name in the value of a `type` keyword that isn't a type,
+/// if it's a `type` keyword that has one.
+fn unknown_type<'a>(keyword: &str, value: &'a Value) -> Option<&'a str> {
+    if keyword != "type" {
+        return None;
+    }
+    match *value {
+        Value::String(ref s) => Some(s.as_str()).filter(|s| Type::from_string(s).is_none()),
+        Value::Array(ref arr) => arr.iter()
+            .filter_map(Value::as_str)
+            .find(|s| Type::from_string(s).is_none()),
+        _ => None,
+    }
+}
+
 /// An error encountered when attempting to validate a
 /// [`Value`](https://docs.rs/serde_json/1.0.2/serde_json/enum.Value.html)
 /// against a [`JsonSchema`](struct.JsonSchema.html).
 #[derive(Clone, Debug, PartialEq)]
 pub enum ValidationError {
+    /// A property not named by `properties` or matched by
+    /// `patternProperties` failed the `additionalProperties` schema.
+    ///
+    /// `key` is the name of the property, and `cause` is the error its value
+    /// failed with. For example, `"additionalProperties": false` results in a
+    /// `cause` of `NoValuesPass`.
+    AdditionalPropertyFailed {
+        /// The name of the property.
+        key: String,
+        /// The error the property's value failed with.
+        cause: Box<ValidationError>,
+    },
+
+    /// A subschema of an `allOf` did not match.
+    ///
+    /// `index` is the position of the failing subschema in the `allOf`, and
+    /// `cause` is the error it failed with. For example, a `false` subschema
+    /// results in a `cause` of `NoValuesPass`.
+    AllOfBranchFailed {
+        /// The index of the subschema that failed.
+        index: usize,
+        /// The error the subschema failed with.
+        cause: Box<ValidationError>,
+    },
+
+    /// None of the subschemas of an `anyOf` matched.
+    ///
+    /// The value holds the error each subschema failed with, in order.
+    AnyOfAllFailed(Vec<ValidationError>),
+
     /// A `$ref` was found pointing to a nonexistent schema.
     BadReference(Url),
 
+    /// No element of an array matched the `contains` subschema.
+    ///
+    /// The value holds the error each element failed with, in order.
+    ContainsNoneMatched(Vec<ValidationError>),
+
     /// A condition specified in a schema was not met.
     ConditionFailed(Condition),
 
+    /// A schema given to
+    /// [`Context::validate_transient`](struct.Context.html#method.validate_transient)
+    /// couldn't be parsed.
+    InvalidSchema(Box<FromValueError>),
+
+    /// A value to be validated could not be parsed as JSON.
+    ///
+    /// The value is a description of the parse error.
+    InvalidJson(String),
+
+    /// An I/O error occurred while reading a value to be validated.
+    ///
+    /// The value is a description of the I/O error.
+    Io(String),
+
     /// A value was provided somewhere no value can exist, for example to the
     /// `false` schema.
     NoValuesPass(Value),
+
+    /// A number was outside of a bound set by `maximum`, `exclusiveMaximum`,
+    /// `minimum`, or `exclusiveMinimum`.
+    NumberOutOfRange {
+        /// The bound that was exceeded.
+        bound: Number,
+        /// The number that exceeded it.
+        actual: Number,
+        /// The keyword that set the bound.
+        kind: BoundKind,
+    },
+
+    /// None of the subschemas of a `oneOf` matched.
+    ///
+    /// The value holds the error each subschema failed with, in order.
+    OneOfAllFailed(Vec<ValidationError>),
+
+    /// More than one of the subschemas of a `oneOf` matched.
+    ///
+    /// The value holds the indices of the subschemas that matched.
+    OneOfManyPassed(Vec<usize>),
+
+    /// A `$ref` led back to itself without descending into the value, as in
+    /// `{"allOf": [{"$ref": "#"}]}`, so following it would never end.
+    ///
+    /// The value is the URI of the reference.
+    ReferenceCycle(Url),
+}
+
+impl ValidationError {
+    /// Renders the error as an indented tree, one line per error, with the
+    /// errors that caused it (e.g. those of the branches of an `anyOf`)
+    /// nested under it. Each line reads
+    /// `at <instance-path>: <message> (schema: <keyword-location>)`, where
+    /// the instance path is a JSON pointer into the value being validated,
+    /// and the keyword location is a JSON pointer into the schema.
+    ///
+    /// Both paths are relative to the schema and value the error came from,
+    /// and only reach as deep as the error records: a failure inside a
+    /// `properties` subschema, for example, is reported at the subschema's
+    /// own root.
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        self.render_into(&mut out, 0, "", "#");
+        out
+    }
+
+    fn render_into(&self, out: &mut String, depth: usize, instance: &str, schema: &str) {
+        let (message, location) = match *self {
+            ValidationError::AdditionalPropertyFailed { ref key, .. } => {
+                (format!("additional property {:?} failed", key), format!("{}/additionalProperties", schema))
+            },
+            ValidationError::AllOfBranchFailed { index, .. } => {
+                (format!("allOf branch {} failed", index), format!("{}/allOf/{}", schema, index))
+            },
+            ValidationError::AnyOfAllFailed(_) => {
+                ("no anyOf branch matched".to_string(), format!("{}/anyOf", schema))
+            },
+            ValidationError::ContainsNoneMatched(_) => {
+                ("no array element matched".to_string(), format!("{}/contains", schema))
+            },
+            ValidationError::ConditionFailed(ref c) => (self.to_string(), format!("{}/{}", schema, c.keyword())),
+            ValidationError::NumberOutOfRange { kind, .. } => (self.to_string(), format!("{}/{}", schema, kind)),
+            ValidationError::OneOfAllFailed(_) => {
+                ("no oneOf branch matched".to_string(), format!("{}/oneOf", schema))
+            },
+            _ => (self.to_string(), schema.to_string()),
+        };
+        let at = if instance.is_empty() { "root" } else { instance };
+        writeln!(out, "{:indent$}at {}: {} (schema: {})", "", at, message, location, indent = depth * 2)
+            .expect("Writing to a String failed");
+
+        match *self {
+            ValidationError::AdditionalPropertyFailed { ref key, ref cause } => {
+                let instance = format!("{}/{}", instance, escape_pointer(key));
+                cause.render_into(out, depth + 1, &instance, &location);
+            },
+            ValidationError::AllOfBranchFailed { ref cause, .. } => {
+                cause.render_into(out, depth + 1, instance, &location);
+            },
+            ValidationError::AnyOfAllFailed(ref errors) |
+            ValidationError::OneOfAllFailed(ref errors) => for (i, err) in errors.iter().enumerate() {
+                err.render_into(out, depth + 1, instance, &format!("{}/{}", location, i));
+            },
+            ValidationError::ContainsNoneMatched(ref errors) => for (i, err) in errors.iter().enumerate() {
+                err.render_into(out, depth + 1, &format!("{}/{}", instance, i), &location);
+            },
+            _ => {},
+        }
+    }
+
+    /// Returns whether this error is the same kind of failure as `other`,
+    /// ignoring details that depend on the value being validated. This is
+    /// meant for tests, which can then assert that e.g. a `maximum` failed
+    /// without pinning the number that exceeded it.
+    ///
+    /// Two errors match when they're the same variant and:
+    ///
+    /// - for `ConditionFailed`, their conditions have the same keyword;
+    /// - for `NumberOutOfRange`, they have the same `kind`;
+    /// - for `AllOfBranchFailed`, they have the same `index`;
+    /// - for `BadReference`, `OneOfManyPassed`, and `ReferenceCycle`, their
+    ///   payloads are equal;
+    /// - for variants with causes, the causes match pairwise in the same way.
+    ///
+    /// Everything else is ignored: the property name of an
+    /// `AdditionalPropertyFailed`, the values in `NoValuesPass`, the numbers
+    /// in `NumberOutOfRange`, and the descriptions in `InvalidJson`,
+    /// `InvalidSchema`, and `Io`. Use `==` to compare errors exactly.
+    pub fn matches_kind(&self, other: &ValidationError) -> bool {
+        match (self, other) {
+            (&ValidationError::AdditionalPropertyFailed { cause: ref a, .. },
+             &ValidationError::AdditionalPropertyFailed { cause: ref b, .. }) => a.matches_kind(b),
+            (&ValidationError::AllOfBranchFailed { index: i, cause: ref a },
+             &ValidationError::AllOfBranchFailed { index: j, cause: ref b }) => i == j && a.matches_kind(b),
+            (&ValidationError::AnyOfAllFailed(ref a), &ValidationError::AnyOfAllFailed(ref b)) |
+            (&ValidationError::ContainsNoneMatched(ref a), &ValidationError::ContainsNoneMatched(ref b)) |
+            (&ValidationError::OneOfAllFailed(ref a), &ValidationError::OneOfAllFailed(ref b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.matches_kind(b))
+            },
+            (&ValidationError::BadReference(ref a), &ValidationError::BadReference(ref b)) => a == b,
+            (&ValidationError::ConditionFailed(ref a), &ValidationError::ConditionFailed(ref b)) => {
+                a.keyword() == b.keyword()
+            },
+            (&ValidationError::InvalidJson(_), &ValidationError::InvalidJson(_)) |
+            (&ValidationError::InvalidSchema(_), &ValidationError::InvalidSchema(_)) |
+            (&ValidationError::Io(_), &ValidationError::Io(_)) |
+            (&ValidationError::NoValuesPass(_), &ValidationError::NoValuesPass(_)) => true,
+            (&ValidationError::NumberOutOfRange { kind: a, .. },
+             &ValidationError::NumberOutOfRange { kind: b, .. }) => a == b,
+            (&ValidationError::OneOfManyPassed(ref a), &ValidationError::OneOfManyPassed(ref b)) => a == b,
+            (&ValidationError::ReferenceCycle(ref a), &ValidationError::ReferenceCycle(ref b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns whether this error (or an error that caused it) comes from
+    /// being unable to resolve a schema, rather than from the value failing
+    /// to validate. Such errors shouldn't be treated as an ordinary failure
+    /// by keywords like `not`, which invert the result of validation.
+    pub(crate) fn is_resolution_error(&self) -> bool {
+        match *self {
+            ValidationError::AdditionalPropertyFailed { ref cause, .. } |
+            ValidationError::AllOfBranchFailed { ref cause, .. } => cause.is_resolution_error(),
+            ValidationError::AnyOfAllFailed(ref errors) |
+            ValidationError::ContainsNoneMatched(ref errors) |
+            ValidationError::OneOfAllFailed(ref errors) => errors.iter().any(|e| e.is_resolution_error()),
+            ValidationError::BadReference(_) | ValidationError::ReferenceCycle(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match *self {
+            ValidationError::AdditionalPropertyFailed { ref key, ref cause } => {
+                write!(fmt, "additional property {:?} failed: {}", key, cause)
+            },
+            ValidationError::AllOfBranchFailed { index, ref cause } => {
+                write!(fmt, "allOf branch {} failed: {}", index, cause)
+            },
+            ValidationError::AnyOfAllFailed(ref errors) => {
+                write!(fmt, "no anyOf branch matched: ")?;
+                write_errors(fmt, errors)
+            },
+            ValidationError::BadReference(ref uri) => write!(fmt, "couldn't resolve reference to {}", uri),
+            ValidationError::ConditionFailed(ref condition) => write!(fmt, "failed {}", condition),
+            ValidationError::ContainsNoneMatched(ref errors) => {
+                if errors.is_empty() {
+                    return write!(fmt, "no array element matched, since the array is empty");
+                }
+                write!(fmt, "no array element matched (")?;
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(fmt, "; ")?;
+                    }
+                    write!(fmt, "element {}: {}", i, err)?;
+                }
+                write!(fmt, ")")
+            },
+            ValidationError::InvalidJson(ref err) => write!(fmt, "invalid JSON: {}", err),
+            ValidationError::InvalidSchema(ref err) => write!(fmt, "invalid schema: {}", err),
+            ValidationError::Io(ref err) => write!(fmt, "I/O error: {}", err),
+            ValidationError::NoValuesPass(ref value) => write!(fmt, "{} given where no value can pass", value),
+            ValidationError::NumberOutOfRange { ref bound, ref actual, kind } => {
+                let relation = match kind {
+                    BoundKind::Maximum => "exceeds",
+                    BoundKind::ExclusiveMaximum => "is not less than",
+                    BoundKind::Minimum => "is less than",
+                    BoundKind::ExclusiveMinimum => "is not greater than",
+                };
+                write!(fmt, "{} {} {} {}", actual, relation, kind, bound)
+            },
+            ValidationError::OneOfAllFailed(ref errors) => {
+                write!(fmt, "no oneOf branch matched: ")?;
+                write_errors(fmt, errors)
+            },
+            ValidationError::OneOfManyPassed(ref indices) => {
+                write!(fmt, "more than one oneOf branch matched: {:?}", indices)
+            },
+            ValidationError::ReferenceCycle(ref uri) => write!(fmt, "reference to {} never descends into the value", uri),
+        }
+    }
+}
+
+/// Only the errors with a single cause have a `source`; the ones with a cause
+/// per branch or element (like `AnyOfAllFailed`) don't, since it couldn't
+/// return all of them.
+impl StdError for ValidationError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            ValidationError::AdditionalPropertyFailed { ref cause, .. } |
+            ValidationError::AllOfBranchFailed { ref cause, .. } => Some(&**cause),
+            ValidationError::InvalidSchema(ref err) => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+/// Escapes a key for use as a JSON pointer component.
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Writes the errors as a semicolon-separated list in square brackets.
+fn write_errors(fmt: &mut Formatter, errors: &[ValidationError]) -> FmtResult {
+    write!(fmt, "[")?;
+    for (i, err) in errors.iter().enumerate() {
+        if i > 0 {
+            write!(fmt, "; ")?;
+        }
+        write!(fmt, "{}", err)?;
+    }
+    write!(fmt, "]")
+}
+
+/// The kind of bound in a
+/// [`ValidationError::NumberOutOfRange`](enum.ValidationError.html#variant.NumberOutOfRange).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BoundKind {
+    /// The `maximum` keyword; the number must be at most the bound.
+    Maximum,
+    /// The `exclusiveMaximum` keyword; the number must be less than the bound.
+    ExclusiveMaximum,
+    /// The `minimum` keyword; the number must be at least the bound.
+    Minimum,
+    /// The `exclusiveMinimum` keyword; the number must be greater than the
+    /// bound.
+    ExclusiveMinimum,
+}
+
+impl BoundKind {
+    /// Returns the keyword that sets this kind of bound.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            BoundKind::Maximum => "maximum",
+            BoundKind::ExclusiveMaximum => "exclusiveMaximum",
+            BoundKind::Minimum => "minimum",
+            BoundKind::ExclusiveMinimum => "exclusiveMinimum",
+        }
+    }
+}
+
+impl Display for BoundKind {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.write_str(self.as_str())
+    }
+}
+
+/// Any error this crate produces, for callers that create schemas and
+/// validate values in the same place. Both `FromValueError` and
+/// `ValidationError` convert into it, so `?` can be used on either.
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// An error creating a schema.
+    FromValue(FromValueError),
+
+    /// An error validating a value.
+    Validation(ValidationError),
+
+    /// A valid value couldn't be deserialized, from
+    /// [`JsonSchema::validate_into`](struct.JsonSchema.html#method.validate_into).
+    ///
+    /// The error is in an `Arc` so that this type can still be cloned.
+    Deserialize(Arc<::serde_json::Error>),
+}
+
+impl Display for Error {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match *self {
+            Error::FromValue(ref err) => write!(fmt, "invalid schema: {}", err),
+            Error::Validation(ref err) => Display::fmt(err, fmt),
+            Error::Deserialize(ref err) => write!(fmt, "couldn't deserialize valid value: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Error::FromValue(ref err) => Some(err),
+            Error::Validation(ref err) => Some(err),
+            Error::Deserialize(ref err) => Some(&**err),
+        }
+    }
+}
+
+/// `serde_json`'s errors can't be compared, so `Deserialize` errors are equal
+/// when their messages are.
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (&Error::FromValue(ref a), &Error::FromValue(ref b)) => a == b,
+            (&Error::Validation(ref a), &Error::Validation(ref b)) => a == b,
+            (&Error::Deserialize(ref a), &Error::Deserialize(ref b)) => a.to_string() == b.to_string(),
+            _ => false,
+        }
+    }
+}
+
+impl From<FromValueError> for Error {
+    fn from(err: FromValueError) -> Error {
+        Error::FromValue(err)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(err: ValidationError) -> Error {
+        Error::Validation(err)
+    }
 }
@@ -0,0 +1,233 @@
+//! The conditions that can be checked without the `std` feature.
+//!
+//! Everything else in the crate holds `serde_json`, `url`, and `regex` types,
+//! which need `std`. The conditions here don't refer to subschemas or use
+//! regexes, so they only need `core` and `alloc`, and are checked against a
+//! [`LeafValue`](enum.LeafValue.html) rather than a `serde_json::Value`.
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use serde_json::Value;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// The type of a JSON value.
+///
+/// Under this definition of type, a value may have more than one type. For
+/// example, `4` has both the type `Integer` and the type `Number`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Type {
+    /// The type of the `null` value.
+    Null,
+    /// The type of `true` and `false`.
+    Boolean,
+    /// The type of all numbers.
+    Number,
+    /// The type of all integers, including numbers written with a zero
+    /// fractional part, like `1.0` or `1e20`.
+    ///
+    /// Integers outside the range of an `i64` or `u64` are parsed as `f64`s,
+    /// and so lose precision. Every `f64` that large is integral, so for
+    /// example `100000000000000000000.5` counts as an integer too.
+    Integer,
+    /// The type of all strings.
+    String,
+    /// The type of all arrays.
+    Array,
+    /// The type of all objects.
+    Object,
+}
+
+impl Type {
+    /// Every type, in the order the spec lists them.
+    pub const ALL: [Type; 7] = [
+        Type::Null,
+        Type::Boolean,
+        Type::Number,
+        Type::Integer,
+        Type::String,
+        Type::Array,
+        Type::Object,
+    ];
+
+    /// Tries to convert the string to a Type, returning None if it does not
+    /// match.
+    pub fn from_string(s: &str) -> Option<Type> {
+        match s {
+            "null" => Some(Type::Null),
+            "boolean" => Some(Type::Boolean),
+            "number" => Some(Type::Number),
+            "integer" => Some(Type::Integer),
+            "string" => Some(Type::String),
+            "array" => Some(Type::Array),
+            "object" => Some(Type::Object),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the type, as used by the `type` keyword. This is
+    /// the inverse of `from_string`.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Number => "number",
+            Type::Integer => "integer",
+            Type::String => "string",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.write_str(self.as_str())
+    }
+}
+
+/// A JSON value, for checking a [`LeafCondition`](enum.LeafCondition.html)
+/// against.
+///
+/// Numbers are `f64`s, so integers beyond 2<sup>53</sup> lose precision.
+/// With the `std` feature, a `serde_json::Value` can be converted to one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeafValue {
+    /// The `null` value.
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A number.
+    Number(f64),
+    /// A string.
+    String(String),
+    /// An array.
+    Array(Vec<LeafValue>),
+    /// An object.
+    Object(BTreeMap<String, LeafValue>),
+}
+
+impl LeafValue {
+    /// Returns whether the value has the given type.
+    pub fn has_type(&self, ty: Type) -> bool {
+        match (ty, self) {
+            (Type::Null, &LeafValue::Null) => true,
+            (Type::Boolean, &LeafValue::Bool(_)) => true,
+            (Type::Number, &LeafValue::Number(_)) => true,
+            (Type::Integer, &LeafValue::Number(n)) => n % 1.0 == 0.0,
+            (Type::String, &LeafValue::String(_)) => true,
+            (Type::Array, &LeafValue::Array(_)) => true,
+            (Type::Object, &LeafValue::Object(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<&'a Value> for LeafValue {
+    fn from(json: &'a Value) -> LeafValue {
+        match *json {
+            Value::Null => LeafValue::Null,
+            Value::Bool(b) => LeafValue::Bool(b),
+            Value::Number(ref n) => LeafValue::Number(n.as_f64().expect("Number wasn't f64")),
+            Value::String(ref s) => LeafValue::String(s.clone()),
+            Value::Array(ref arr) => LeafValue::Array(arr.iter().map(LeafValue::from).collect()),
+            Value::Object(ref obj) => LeafValue::Object(obj.iter()
+                .map(|(k, v)| (k.clone(), LeafValue::from(v)))
+                .collect()),
+        }
+    }
+}
+
+/// A condition that doesn't refer to any subschemas, and so can be checked
+/// without the `std` feature. These behave like the
+/// [`Condition`](enum.Condition.html) variants of the same names.
+///
+/// Numbers are compared as `f64`s, and `MultipleOf` allows the same rounding
+/// error as `Condition::MultipleOf` does for fractional numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeafCondition {
+    /// The value must be a multiple of the number, if it's a number.
+    MultipleOf(f64),
+    /// The value must not be greater than the number, if it's a number.
+    Maximum(f64),
+    /// The value must be less than the number, if it's a number.
+    ExclusiveMaximum(f64),
+    /// The value must not be less than the number, if it's a number.
+    Minimum(f64),
+    /// The value must be greater than the number, if it's a number.
+    ExclusiveMinimum(f64),
+    /// The value must not be longer than this many characters, if it's a
+    /// string.
+    MaxLength(u64),
+    /// The value must not be shorter than this many characters, if it's a
+    /// string.
+    MinLength(u64),
+    /// The value must not have more items than this, if it's an array.
+    MaxItems(u64),
+    /// The value must not have fewer items than this, if it's an array.
+    MinItems(u64),
+    /// The value must not have more properties than this, if it's an object.
+    MaxProperties(u64),
+    /// The value must not have fewer properties than this, if it's an object.
+    MinProperties(u64),
+    /// The value must have all of these properties, if it's an object.
+    Required(Vec<String>),
+    /// The value must be equal to this one.
+    Const(LeafValue),
+    /// The value must be equal to one of these.
+    Enum(Vec<LeafValue>),
+    /// The value must have one of these types.
+    Type(Vec<Type>),
+}
+
+impl LeafCondition {
+    /// Returns whether the value passes the condition.
+    pub fn holds(&self, json: &LeafValue) -> bool {
+        match (self, json) {
+            (&LeafCondition::MultipleOf(m), &LeafValue::Number(n)) => {
+                let q = round(n / m);
+                q.is_finite() && abs(n - m * q) <= abs(n) * ::std::f64::EPSILON * 4.0
+            },
+            (&LeafCondition::Maximum(m), &LeafValue::Number(n)) => n <= m,
+            (&LeafCondition::ExclusiveMaximum(m), &LeafValue::Number(n)) => n < m,
+            (&LeafCondition::Minimum(m), &LeafValue::Number(n)) => n >= m,
+            (&LeafCondition::ExclusiveMinimum(m), &LeafValue::Number(n)) => n > m,
+            (&LeafCondition::MaxLength(m), &LeafValue::String(ref s)) => s.chars().count() as u64 <= m,
+            (&LeafCondition::MinLength(m), &LeafValue::String(ref s)) => s.chars().count() as u64 >= m,
+            (&LeafCondition::MaxItems(m), &LeafValue::Array(ref arr)) => arr.len() as u64 <= m,
+            (&LeafCondition::MinItems(m), &LeafValue::Array(ref arr)) => arr.len() as u64 >= m,
+            (&LeafCondition::MaxProperties(m), &LeafValue::Object(ref obj)) => obj.len() as u64 <= m,
+            (&LeafCondition::MinProperties(m), &LeafValue::Object(ref obj)) => obj.len() as u64 >= m,
+            (&LeafCondition::Required(ref props), &LeafValue::Object(ref obj)) => {
+                props.iter().all(|p| obj.contains_key(p))
+            },
+            (&LeafCondition::Const(ref v), _) => json == v,
+            (&LeafCondition::Enum(ref vs), _) => vs.contains(json),
+            (&LeafCondition::Type(ref types), _) => types.iter().any(|&ty| json.has_type(ty)),
+            _ => true,
+        }
+    }
+}
+
+/// Returns the absolute value of the number; `f64::abs` needs `std`.
+fn abs(n: f64) -> f64 {
+    if n < 0.0 { -n } else { n }
+}
+
+/// Rounds the number to the nearest integer, with halves rounded away from
+/// zero; `f64::round` needs `std`.
+fn round(n: f64) -> f64 {
+    let whole = n - n % 1.0;
+    if abs(n - whole) >= 0.5 {
+        if n < 0.0 { whole - 1.0 } else { whole + 1.0 }
+    } else {
+        whole
+    }
+}
@@ -7,19 +7,66 @@
 //! [![Documentation](https://docs.rs/json-schema/badge.svg)](https://docs.rs/json-schema)
 //! 
 //! **TODO Document**
+//!
+//! Everything but [`LeafCondition`](enum.LeafCondition.html),
+//! [`LeafValue`](enum.LeafValue.html), and [`Type`](enum.Type.html) needs the
+//! `std` feature, which is on by default. Without it, the crate is `no_std`,
+//! and only needs `alloc`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+#[cfg(feature = "std")]
 extern crate either;
+#[cfg(feature = "std")]
 extern crate json_pointer;
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate lazy_static;
+#[cfg(feature = "std")]
 extern crate regex;
+#[cfg(feature = "std")]
+extern crate serde;
+#[cfg(feature = "std")]
 extern crate serde_json;
+#[cfg(feature = "std")]
 extern crate url;
 
+#[cfg(feature = "std")]
+mod annotations;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
 mod errors;
+mod leaf;
+#[cfg(feature = "std")]
+mod lint;
+#[cfg(feature = "std")]
 mod schema;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+mod tree;
 
-pub use errors::{FromValueError, ValidationError};
-pub use schema::{Condition, Context, JsonSchema, Type};
+#[cfg(feature = "std")]
+pub use annotations::Annotations;
+#[cfg(feature = "std")]
+pub use builder::SchemaBuilder;
+#[cfg(feature = "std")]
+pub use errors::{BoundKind, Error, FromValueError, ValidationError};
+pub use leaf::{LeafCondition, LeafValue, Type};
+#[cfg(feature = "std")]
+pub use lint::{Diagnostic, Lint};
+#[cfg(feature = "std")]
+pub use schema::{is_well_formed, schemas_equivalent, type_schema, Condition, Context, Discriminator,
+    FrozenContext, JsonSchema, SchemaId};
+#[cfg(feature = "metaschema")]
+pub use schema::{validate_schema, METASCHEMA_URI, METASCHEMA_VALUE};
+#[cfg(feature = "std")]
+pub use stream::{ValidateNdjson, ValidateReader};
+#[cfg(feature = "std")]
+pub use tree::ErrorTree;
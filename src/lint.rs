@@ -0,0 +1,31 @@
+use errors::ValidationError;
+use url::Url;
+
+/// A likely mistake in a schema, which doesn't stop it from being used.
+/// Returned by [`JsonSchema::lint`](struct.JsonSchema.html#method.lint).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lint {
+    /// The `default` of the schema with the given URI doesn't pass that
+    /// schema.
+    DefaultViolatesSchema(Url, ValidationError),
+
+    /// The element of `examples` with the given index, in the schema with the
+    /// given URI, doesn't pass that schema.
+    ExampleViolatesSchema(Url, usize, ValidationError),
+}
+
+/// A keyword that was accepted when creating a schema, but isn't enforced
+/// when validating against it. Returned by
+/// [`JsonSchema::diagnostics`](struct.JsonSchema.html#method.diagnostics).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Diagnostic {
+    /// The schema has a `format` keyword with the given format, which isn't
+    /// checked (but see
+    /// [`JsonSchema::validate_annotated`](struct.JsonSchema.html#method.validate_annotated)).
+    FormatNotEnforced(String),
+
+    /// The schema has the given keyword, which isn't defined by the
+    /// specification, so it was ignored. This is only reported when the
+    /// [`Context`](struct.Context.html) isn't set to reject such keywords.
+    UnknownKeyword(String),
+}
@@ -1,22 +1,30 @@
+use annotations::Annotations;
 use either::Either;
-use errors::ValidationError;
+use errors::{BoundKind, ValidationError};
+use leaf::Type;
 use regex::Regex;
-use serde_json::{Number, Value};
-use std::collections::BTreeMap;
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeMap, HashSet};
 use std::cmp::Ordering;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::mem::discriminant;
 use std::ops::Deref;
-use super::Context;
+use super::{Context, Validator};
+use super::equality::{canonical_number, CanonicalValue, json_eq, number_cmp, number_is_integral};
+use super::validator::check_all;
+use tree::ErrorTree;
 use url::Url;
 
 /// A single constraint put on a value by a schema.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Condition {
-    /// If the type is a number, it must be an integer and a multiple of the
-    /// given number.
+    /// If the given value is a number, dividing it by the given number must
+    /// result in an integer.
     ///
     /// Defined in [Section 6.1 of the Validation
     /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.1)
-    MultipleOf(u64),
+    MultipleOf(Number),
 
     /// If the given value is a number, it must not be greater than the given
     /// number.
@@ -90,39 +98,188 @@ pub enum Condition {
     /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.12).
     MinItems(u64),
 
-    #[doc(hidden)] // TODO
+    /// If the given value is an array and the given boolean is true, all of
+    /// the array's items must be distinct.
+    ///
+    /// Items are compared the same way as by `Const`, so `1` and `1.0` are
+    /// not distinct.
+    ///
+    /// Defined in [Section 6.13 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.13).
     UniqueItems(bool),
-    #[doc(hidden)] // TODO
-    Contains(Url),
-    #[doc(hidden)] // TODO
+
+    /// If the given value is an array, at least one of its items must
+    /// validate against the given schema.
+    ///
+    /// The second and third values are the `minContains` and `maxContains`
+    /// keywords, which are an extension from later drafts: if present, the
+    /// number of items that validate must be at least the first (instead of
+    /// one) and at most the second. They're only parsed with the
+    /// `contains-count` feature, so without it both are always `None`.
+    ///
+    /// Defined in [Section 6.14 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.14).
+    Contains(Url, Option<u64>, Option<u64>),
+
+    /// If the given value is an object, it must not have more properties than
+    /// the given number.
+    ///
+    /// Defined in [Section 6.15 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.15).
     MaxProperties(u64),
-    #[doc(hidden)] // TODO
+
+    /// If the given value is an object, it must not have fewer properties
+    /// than the given number.
+    ///
+    /// Defined in [Section 6.16 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.16).
     MinProperties(u64),
-    #[doc(hidden)] // TODO
+
+    /// If the given value is an object, it must have all of the given
+    /// properties.
+    ///
+    /// Defined in [Section 6.17 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.17).
     Required(Vec<String>),
-    #[doc(hidden)] // TODO
+
+    /// If the given value is an object, each of its properties must validate
+    /// against the schema for that property name, if there is one, and
+    /// against the schema of every regex that matches the property name. Any
+    /// property that has no schema by either of those must validate against
+    /// the other schema, if it is present.
+    ///
+    /// This cooresponds to the `properties`, `patternProperties`, and
+    /// `additionalProperties` keywords.
+    ///
+    /// Defined in [Sections 6.18](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.18),
+    /// [6.19](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.19),
+    /// and [6.20](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.20)
+    /// of the Validation RFC.
     Properties(BTreeMap<String, Url>, BTreeMap<RegexWrapper, Url>, Option<Url>),
-    #[doc(hidden)] // TODO
-    Dependencies(BTreeMap<String, Either<String, Url>>),
-    #[doc(hidden)] // TODO
+
+    /// If the given value is an object, then for each of its properties that
+    /// is a key in the map, either all of the properties in the `Vec` must be
+    /// present, or the whole object must validate against the schema.
+    ///
+    /// Defined in [Section 6.21 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.21).
+    Dependencies(BTreeMap<String, Either<Vec<String>, Url>>),
+
+    /// If the given value is an object, each of its property names must
+    /// validate against the given schema, as a string.
+    ///
+    /// Defined in [Section 6.22 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.22).
     PropertyNames(Url),
-    #[doc(hidden)] // TODO
+
+    /// The given value must be equal to one of the given values.
+    ///
+    /// Numbers are compared by value, so `1` and `1.0` are equal.
+    ///
+    /// Defined in [Section 6.23 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.23).
     Enum(Vec<Value>),
-    #[doc(hidden)] // TODO
+
+    /// The given value must be equal to the given value.
+    ///
+    /// Numbers are compared by value, so `1` and `1.0` are equal.
+    ///
+    /// Defined in [Section 6.24 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.24).
     Const(Value),
-    #[doc(hidden)] // TODO
+
+    /// The given value must be of at least one of the given types.
+    ///
+    /// Defined in [Section 6.25 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.25).
     Type(Vec<Type>),
-    #[doc(hidden)] // TODO
-    AllOf(Vec<Url>),
-    #[doc(hidden)] // TODO
+
+    /// The given value must validate against all of the given schemas.
+    ///
+    /// The second value is the index of the first schema that was known to
+    /// be `false` when this was parsed, if any. Validation then fails at that
+    /// schema without running the ones after it. A `$ref` to a schema that
+    /// hadn't been parsed yet isn't caught, but still fails when it's
+    /// reached.
+    ///
+    /// Defined in [Section 6.26 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.26).
+    AllOf(Vec<Url>, Option<usize>),
+
+    /// The given value must validate against at least one of the given
+    /// schemas.
+    ///
+    /// Defined in [Section 6.27 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.27).
     AnyOf(Vec<Url>),
-    #[doc(hidden)] // TODO
-    OneOf(Vec<Url>),
-    #[doc(hidden)] // TODO
+
+    /// The given value must validate against exactly one of the given
+    /// schemas.
+    ///
+    /// If there's a [`Discriminator`](struct.Discriminator.html) that selects
+    /// a schema for the value, the value is only validated against that
+    /// schema instead.
+    ///
+    /// Defined in [Section 6.28 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.28).
+    OneOf(Vec<Url>, Option<Discriminator>),
+
+    /// The given value must not validate against the given schema.
+    ///
+    /// Defined in [Section 6.29 of the Validation
+    /// RFC](https://tools.ietf.org/html/draft-wright-json-schema-validation-01#section-6.29).
     Not(Url),
+
+    /// If the given value validates against the first schema, it must also
+    /// validate against the second schema, if present. Otherwise, it must
+    /// validate against the third schema, if present.
+    ///
+    /// This cooresponds to the `if`, `then`, and `else` keywords, and is only
+    /// available with the `draft7` feature.
+    ///
+    /// Defined in [Section 6.6 of the draft-07 Validation
+    /// RFC](https://tools.ietf.org/html/draft-handrews-json-schema-validation-01#section-6.6).
+    #[cfg(feature = "draft7")]
+    If(Url, Option<Url>, Option<Url>),
 }
 
 impl Condition {
+    /// Returns the name of the keyword this condition was created from. For
+    /// conditions created from several keywords (such as `Items`, which comes
+    /// from `items` and `additionalItems`), this is the primary one.
+    pub fn keyword(&self) -> &'static str {
+        match *self {
+            Condition::MultipleOf(..) => "multipleOf",
+            Condition::Maximum(..) => "maximum",
+            Condition::ExclusiveMaximum(..) => "exclusiveMaximum",
+            Condition::Minimum(..) => "minimum",
+            Condition::ExclusiveMinimum(..) => "exclusiveMinimum",
+            Condition::MaxLength(..) => "maxLength",
+            Condition::MinLength(..) => "minLength",
+            Condition::Pattern(..) => "pattern",
+            Condition::Items(..) => "items",
+            Condition::MaxItems(..) => "maxItems",
+            Condition::MinItems(..) => "minItems",
+            Condition::UniqueItems(..) => "uniqueItems",
+            Condition::Contains(..) => "contains",
+            Condition::MaxProperties(..) => "maxProperties",
+            Condition::MinProperties(..) => "minProperties",
+            Condition::Required(..) => "required",
+            Condition::Properties(..) => "properties",
+            Condition::Dependencies(..) => "dependencies",
+            Condition::PropertyNames(..) => "propertyNames",
+            Condition::Enum(..) => "enum",
+            Condition::Const(..) => "const",
+            Condition::Type(..) => "type",
+            Condition::AllOf(..) => "allOf",
+            Condition::AnyOf(..) => "anyOf",
+            Condition::OneOf(..) => "oneOf",
+            Condition::Not(..) => "not",
+            #[cfg(feature = "draft7")]
+            Condition::If(..) => "if",
+        }
+    }
+
     /// Priority is essentially the notion of failing early rather than late --
     /// the more values a condition rules out, the higher its priority. This is
     /// so that sorting a list of `Condition`s with this as a key function will
@@ -132,77 +289,640 @@ impl Condition {
     /// For reference, 0 represents the highest priority and `std::usize::MAX`
     /// represents the lowest. (That is, the cheaper and most likely to fail
     /// checks should have numerically lower priorities.)
+    ///
+    /// The priorities fall into tiers:
+    ///
+    ///  - 0: `type`, which rules out the most values for the least work.
+    ///  - 10: checks that look at a single number, length, or count, or
+    ///    compare against fixed values, and never evaluate a subschema.
+    ///  - 20: `pattern`, which runs a regex over the string, and `items` and
+    ///    `properties`, which evaluate a subschema per element.
+    ///  - 50: checks that look at every element together (`uniqueItems`), or
+    ///    evaluate a subschema against many elements without being likely
+    ///    to fail (`contains`, `dependencies`, and `propertyNames`).
+    ///  - 100: the combinators, which evaluate whole subschemas against the
+    ///    value itself.
     pub fn priority(&self) -> usize {
         match *self {
             Condition::Type(..) => 0,
+            Condition::Const(..) => 10,
+            Condition::Enum(..) => 10,
             Condition::ExclusiveMaximum(..) => 10,
             Condition::ExclusiveMinimum(..) => 10,
+            Condition::MaxItems(..) => 10,
             Condition::MaxLength(..) => 10,
+            Condition::MaxProperties(..) => 10,
             Condition::Maximum(..) => 10,
+            Condition::MinItems(..) => 10,
             Condition::MinLength(..) => 10,
+            Condition::MinProperties(..) => 10,
             Condition::Minimum(..) => 10,
+            Condition::MultipleOf(..) => 10,
             Condition::Required(..) => 10,
+            Condition::Items(..) => 20,
+            Condition::Pattern(..) => 20,
             Condition::Properties(..) => 20,
+            Condition::Contains(..) => 50,
+            Condition::Dependencies(..) => 50,
+            Condition::PropertyNames(..) => 50,
+            Condition::UniqueItems(..) => 50,
             Condition::AllOf(..) => 100,
             Condition::AnyOf(..) => 100,
-            _ => {
-                println!("No priority set for {:?}, will default to 1000", self);
-                1000
+            Condition::Not(..) => 100,
+            Condition::OneOf(..) => 100,
+            #[cfg(feature = "draft7")]
+            Condition::If(..) => 100,
+        }
+    }
+
+    /// Returns the URIs of all the subschemas this condition refers to.
+    pub(crate) fn subschemas(&self) -> Vec<&Url> {
+        match *self {
+            Condition::Items(ref items, ref additional) => items.iter()
+                .chain(additional)
+                .collect(),
+            Condition::Properties(ref props, ref patterns, ref additional) => props.values()
+                .chain(patterns.values())
+                .chain(additional)
+                .collect(),
+            Condition::Dependencies(ref deps) => deps.values()
+                .filter_map(|dep| dep.as_ref().right())
+                .collect(),
+            Condition::AllOf(ref urls, _) |
+            Condition::AnyOf(ref urls) => urls.iter().collect(),
+            Condition::OneOf(ref urls, ref discriminator) => urls.iter()
+                .chain(discriminator.iter().flat_map(|d| d.mapping.values()))
+                .collect(),
+            Condition::Contains(ref url, _, _) |
+            Condition::Not(ref url) |
+            Condition::PropertyNames(ref url) => vec![url],
+            #[cfg(feature = "draft7")]
+            Condition::If(ref cond, ref then, ref else_) => Some(cond).into_iter()
+                .chain(then)
+                .chain(else_)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the children of the value that `items` or `properties` apply a
+    /// subschema to, in order, with the subschema's URI and whether it's
+    /// `additionalItems` or `additionalProperties`. A child with more than one
+    /// subschema (e.g. a property that matches a `patternProperties` regex as
+    /// well) is returned once for each. Other conditions have none.
+    fn children<'a, 'b>(&'a self, json: &'b Value) -> impl Iterator<Item = (Child<'b>, &'b Value, &'a Url, bool)> {
+        let items = match (self, json) {
+            (&Condition::Items(ref items, ref additional), &Value::Array(ref arr)) => {
+                Some(arr.iter().enumerate().filter_map(move |(i, json)| {
+                    match items.get(i) {
+                        Some(url) => Some((Child::Index(i), json, url, false)),
+                        None => additional.as_ref().map(|url| (Child::Index(i), json, url, true)),
+                    }
+                }))
+            },
+            _ => None,
+        };
+        let properties = match (self, json) {
+            (&Condition::Properties(ref props, ref patterns, ref additional), &Value::Object(ref obj)) => {
+                Some(obj.iter().flat_map(move |(k, json)| {
+                    let mut matched = props.get(k).into_iter()
+                        .chain(patterns.iter().filter(move |&(re, _)| re.is_match(k)).map(|(_, url)| url))
+                        .peekable();
+                    let additional = if matched.peek().is_none() { additional.as_ref() } else { None };
+                    matched.map(|url| (url, false))
+                        .chain(additional.map(|url| (url, true)))
+                        .map(move |(url, is_additional)| (Child::Key(k), json, url, is_additional))
+                }))
+            },
+            _ => None,
+        };
+        items.into_iter().flatten().chain(properties.into_iter().flatten())
+    }
+
+    /// Coerces strings in the value to the types the schema asks for, as
+    /// described in `Context::set_coerce_types`. Only `type` coerces a value
+    /// itself; `items`, `properties`, and `allOf` coerce the values their
+    /// subschemas apply to. Other keywords may or may not apply, so they're
+    /// left alone.
+    pub(crate) fn coerce(&self, context: &Context, json: &mut Value) {
+        match *self {
+            Condition::AllOf(ref urls, _) => for url in urls {
+                coerce_with(context, url, json);
+            },
+            Condition::Items(..) | Condition::Properties(..) => {
+                let children = self.children(json)
+                    .map(|(child, _, url, _)| (child.to_string(), url))
+                    .collect::<Vec<_>>();
+                for (child, url) in children {
+                    if let Some(json) = child_mut(json, &child) {
+                        coerce_with(context, url, json);
+                    }
+                }
+            },
+            Condition::Type(ref types) if !types.iter().any(|t| t.type_of(json)) => {
+                if let Some(value) = coerce_to(types, json) {
+                    *json = value;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Adds the errors from validating the value to the tree, as described in
+    /// `JsonSchema::validate_tree`. `items`, `properties`, and `allOf` add the
+    /// errors of their subschemas; other conditions add at most one error.
+    pub(crate) fn validate_tree(&self, context: &Context, json: &Value, tree: &mut ErrorTree) {
+        match (self, json) {
+            (&Condition::AllOf(ref urls, _), _) => for url in urls {
+                validate_tree_with(context, url, json, tree);
+            },
+            (&Condition::Items(..), _) | (&Condition::Properties(..), _) => {
+                for (child, json, url, _) in self.children(json) {
+                    let mut subtree = ErrorTree::default();
+                    validate_tree_with(context, url, json, &mut subtree);
+                    tree.push_child(child.to_string(), subtree);
+                }
+            },
+            _ => if let Err(err) = self.validate(context, json) {
+                tree.push(err);
+            },
+        }
+    }
+
+    /// Passes each error from validating the value to the sink, as described
+    /// in `JsonSchema::validate_each`. Returns false if the sink asked to
+    /// stop.
+    pub(crate) fn validate_each(&self, context: &Context, json: &Value, sink: &mut dyn FnMut(ValidationError) -> bool) -> bool {
+        match *self {
+            Condition::AllOf(ref urls, _) => urls.iter().enumerate().all(|(index, url)| {
+                validate_each_with(context, url, json, sink, &|err| ValidationError::AllOfBranchFailed {
+                    index,
+                    cause: Box::new(err),
+                })
+            }),
+            Condition::Items(..) | Condition::Properties(..) => {
+                self.children(json).all(|(child, json, url, is_additional)| match (child, is_additional) {
+                    (Child::Key(k), true) => validate_each_with(context, url, json, sink, &|err| ValidationError::AdditionalPropertyFailed {
+                        key: k.to_string(),
+                        cause: Box::new(err),
+                    }),
+                    _ => validate_each_with(context, url, json, sink, &|err| err),
+                })
+            },
+            _ => match self.validate(context, json) {
+                Ok(()) => true,
+                Err(err) => sink(err),
             },
         }
     }
 
-    /// Returns key-value pairs cooresponding to this condition.
+    /// Adds the annotations from a value that passed this condition, as
+    /// described in `JsonSchema::validate_annotated`. Only the subschemas the
+    /// value matched are descended into; `not` never contributes any.
+    pub(crate) fn annotate(&self, context: &Context, json: &Value, path: &mut Vec<String>, out: &mut Annotations) {
+        let valid = |url: &Url, json: &Value| {
+            context.get(url).map(|schema| schema.is_valid(json)).unwrap_or(false)
+        };
+        match (self, json) {
+            (&Condition::AllOf(ref urls, _), _) => for url in urls {
+                annotate_with(context, url, json, path, out);
+            },
+            (&Condition::AnyOf(ref urls), _) => for url in urls {
+                if valid(url, json) {
+                    out.push_any_of_match(path, url);
+                    annotate_with(context, url, json, path, out);
+                }
+            },
+            (&Condition::OneOf(ref urls, ref discriminator), _) => {
+                let url = discriminator.as_ref()
+                    .and_then(|d| d.select(json))
+                    .or_else(|| urls.iter().find(|url| valid(url, json)));
+                if let Some(url) = url {
+                    annotate_with(context, url, json, path, out);
+                }
+            },
+            (&Condition::Contains(ref url, _, _), &Value::Array(ref arr)) => for (i, json) in arr.iter().enumerate() {
+                if valid(url, json) {
+                    path.push(i.to_string());
+                    annotate_with(context, url, json, path, out);
+                    path.pop();
+                }
+            },
+            (&Condition::Dependencies(ref deps), &Value::Object(ref obj)) => for (k, dep) in deps {
+                if let (true, &Either::Right(ref url)) = (obj.contains_key(k), dep) {
+                    annotate_with(context, url, json, path, out);
+                }
+            },
+            #[cfg(feature = "draft7")]
+            (&Condition::If(ref cond, ref then, ref else_), _) => {
+                let branch = if valid(cond, json) {
+                    annotate_with(context, cond, json, path, out);
+                    then
+                } else {
+                    else_
+                };
+                if let Some(ref url) = *branch {
+                    annotate_with(context, url, json, path, out);
+                }
+            },
+            (&Condition::Items(..), _) | (&Condition::Properties(..), _) => {
+                for (child, json, url, _) in self.children(json) {
+                    path.push(child.to_string());
+                    annotate_with(context, url, json, path, out);
+                    path.pop();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Returns key-value pairs cooresponding to this condition, as part of
+    /// the schema with the given URI. Subschemas are written out in full.
     ///
-    /// The ability to return multiple pairs is required by the Items condition.
-    /// TODO It might also be required for Properties.
-    fn to_pair(&self) -> (String, Value) {
-        unimplemented!()
+    /// The ability to return multiple pairs is required by the conditions that
+    /// come from more than one keyword, like Items and Properties.
+    pub(crate) fn to_pairs(&self, context: &Context, id: &Url) -> Vec<(String, Value)> {
+        let sub = |url: &Url| subschema_value(context, id, url);
+        let subs = |urls: &[Url]| Value::Array(urls.iter().map(&sub).collect());
+        let pair = |k: &str, v: Value| vec![(k.to_string(), v)];
+        match *self {
+            Condition::MultipleOf(ref n) => pair("multipleOf", Value::Number(n.clone())),
+            Condition::Maximum(ref n) => pair("maximum", Value::Number(n.clone())),
+            Condition::ExclusiveMaximum(ref n) => pair("exclusiveMaximum", Value::Number(n.clone())),
+            Condition::Minimum(ref n) => pair("minimum", Value::Number(n.clone())),
+            Condition::ExclusiveMinimum(ref n) => pair("exclusiveMinimum", Value::Number(n.clone())),
+            Condition::MaxLength(n) => pair("maxLength", Value::from(n)),
+            Condition::MinLength(n) => pair("minLength", Value::from(n)),
+            Condition::Pattern(ref re) => pair("pattern", Value::String(re.as_str().to_string())),
+            Condition::Items(ref items, Some(ref additional)) if items.is_empty() => {
+                pair("items", sub(additional))
+            },
+            Condition::Items(ref items, ref additional) => {
+                let mut pairs = pair("items", subs(items));
+                if let Some(ref additional) = *additional {
+                    pairs.push(("additionalItems".to_string(), sub(additional)));
+                }
+                pairs
+            },
+            Condition::MaxItems(n) => pair("maxItems", Value::from(n)),
+            Condition::MinItems(n) => pair("minItems", Value::from(n)),
+            Condition::UniqueItems(b) => pair("uniqueItems", Value::Bool(b)),
+            Condition::Contains(ref url, min, max) => {
+                let mut pairs = pair("contains", sub(url));
+                if let Some(min) = min {
+                    pairs.push(("minContains".to_string(), Value::from(min)));
+                }
+                if let Some(max) = max {
+                    pairs.push(("maxContains".to_string(), Value::from(max)));
+                }
+                pairs
+            },
+            Condition::MaxProperties(n) => pair("maxProperties", Value::from(n)),
+            Condition::MinProperties(n) => pair("minProperties", Value::from(n)),
+            Condition::Required(ref props) => pair("required", Value::Array(props.iter()
+                .map(|p| Value::String(p.to_string()))
+                .collect())),
+            Condition::Properties(ref props, ref patterns, ref additional) => {
+                let mut pairs = Vec::new();
+                if !props.is_empty() || (patterns.is_empty() && additional.is_none()) {
+                    pairs.push(("properties".to_string(), Value::Object(props.iter()
+                        .map(|(k, url)| (k.to_string(), sub(url)))
+                        .collect())));
+                }
+                if !patterns.is_empty() {
+                    pairs.push(("patternProperties".to_string(), Value::Object(patterns.iter()
+                        .map(|(re, url)| (re.as_str().to_string(), sub(url)))
+                        .collect())));
+                }
+                if let Some(ref additional) = *additional {
+                    pairs.push(("additionalProperties".to_string(), sub(additional)));
+                }
+                pairs
+            },
+            Condition::Dependencies(ref deps) => pair("dependencies", Value::Object(deps.iter()
+                .map(|(k, dep)| (k.to_string(), match *dep {
+                    Either::Left(ref props) => Value::Array(props.iter()
+                        .map(|p| Value::String(p.to_string()))
+                        .collect()),
+                    Either::Right(ref url) => sub(url),
+                }))
+                .collect())),
+            Condition::PropertyNames(ref url) => pair("propertyNames", sub(url)),
+            Condition::Enum(ref vs) => pair("enum", Value::Array(vs.clone())),
+            Condition::Const(ref v) => pair("const", v.clone()),
+            Condition::Type(ref types) => {
+                let value = if types.len() == 1 {
+                    Value::String(types[0].as_str().to_string())
+                } else {
+                    Value::Array(types.iter()
+                        .map(|t| Value::String(t.as_str().to_string()))
+                        .collect())
+                };
+                pair("type", value)
+            },
+            Condition::AllOf(ref urls, _) => pair("allOf", subs(urls)),
+            Condition::AnyOf(ref urls) => pair("anyOf", subs(urls)),
+            Condition::OneOf(ref urls, ref discriminator) => {
+                let mut pairs = pair("oneOf", subs(urls));
+                if let Some(ref discriminator) = *discriminator {
+                    pairs.push(("discriminator".to_string(), discriminator.to_value()));
+                }
+                pairs
+            },
+            Condition::Not(ref url) => pair("not", sub(url)),
+            #[cfg(feature = "draft7")]
+            Condition::If(ref cond, ref then, ref else_) => {
+                let mut pairs = pair("if", sub(cond));
+                if let Some(ref then) = *then {
+                    pairs.push(("then".to_string(), sub(then)));
+                }
+                if let Some(ref else_) = *else_ {
+                    pairs.push(("else".to_string(), sub(else_)));
+                }
+                pairs
+            },
+        }
     }
 
     /// Validates the value with the condition.
     pub fn validate(&self, context: &Context, json: &Value) -> Result<(), ValidationError> {
         let ok = match *self {
-            Condition::AllOf(ref urls) => {
-                for url in urls {
-                    let schema = context.get(url)
-                        .ok_or_else(|| ValidationError::BadReference(url.clone()))?;
-                    schema.validate(json)?
+            Condition::AllOf(ref urls, nothing) => {
+                // Branches are checked in order, and the `false` one fails
+                // every value, so the ones after it can't change the result.
+                let end = nothing.filter(|&i| is_nothing(context, &urls[i]))
+                    .map(|i| i + 1)
+                    .unwrap_or_else(|| urls.len());
+                for (index, url) in urls[..end].iter().enumerate() {
+                    let schema = context.try_get(url)?;
+                    schema.validate(json).map_err(|err| ValidationError::AllOfBranchFailed {
+                        index,
+                        cause: Box::new(err),
+                    })?
                 }
                 true
             },
             Condition::AnyOf(ref urls) => {
+                let mut errors = Vec::with_capacity(urls.len());
                 for url in urls {
-                    let schema = context.get(url)
-                        .ok_or_else(|| ValidationError::BadReference(url.clone()))?;
-                    if schema.validate(json).is_ok() {
-                        return Ok(());
+                    let schema = context.try_get(url)?;
+                    match schema.validate(json) {
+                        Ok(()) => return Ok(()),
+                        Err(err) => errors.push(err),
                     }
                 }
-                false
+                return Err(ValidationError::AnyOfAllFailed(errors));
             },
-            Condition::Const(ref v) => json == v,
-            Condition::Contains(ref uri) => if let Value::Array(ref arr) = *json {
-                let schema = context.get(uri)
-                    .ok_or_else(|| ValidationError::BadReference(uri.clone()))?;
-                arr.iter().any(|v| schema.validate(v).is_ok())
+            Condition::Contains(ref uri, min, max) => if let Value::Array(ref arr) = *json {
+                let schema = context.try_get(uri)?;
+                let min = min.unwrap_or(1);
+                let mut matches = 0;
+                let mut errors = Vec::new();
+                for v in arr {
+                    match schema.validate(v) {
+                        Ok(()) => {
+                            matches += 1;
+                            // Without a maximum, the rest can't matter.
+                            if max.is_none() && matches >= min {
+                                return Ok(());
+                            }
+                        },
+                        Err(err) => if matches == 0 {
+                            errors.push(err);
+                        },
+                    }
+                }
+                if matches == 0 && min > 0 {
+                    return Err(ValidationError::ContainsNoneMatched(errors));
+                }
+                matches >= min && max.map(|max| matches <= max).unwrap_or(true)
             } else {
                 true
             },
-            Condition::ExclusiveMinimum(ref m) => if let Value::Number(ref n) = *json {
-                n > m
+            Condition::Dependencies(ref deps) => if let Value::Object(ref obj) = *json {
+                for (_, dep) in deps.iter().filter(|&(k, _)| obj.contains_key(k)) {
+                    match *dep {
+                        Either::Left(ref props) => if !props.iter().all(|p| obj.contains_key(p)) {
+                            return Err(ValidationError::ConditionFailed(self.clone()));
+                        },
+                        Either::Right(ref url) => {
+                            let schema = context.try_get(url)?;
+                            schema.validate(json)?
+                        },
+                    }
+                }
+                true
             } else {
                 true
             },
-            Condition::Items(ref items, ref additional) => if let Value::Array(ref arr) = *json {
-                for (i, json) in arr.iter().enumerate() {
-                    if let Some(url) = items.get(i).or(additional.as_ref()) {
-                        let schema = context.get(url)
-                            .ok_or_else(|| ValidationError::BadReference(url.clone()))?;
-                        schema.validate(json)?
+            #[cfg(feature = "draft7")]
+            Condition::If(ref cond, ref then, ref else_) => {
+                let schema = context.try_get(cond)?;
+                let branch = match schema.validate(json) {
+                    Ok(()) => then.as_ref(),
+                    Err(err) => if err.is_resolution_error() {
+                        return Err(err);
+                    } else {
+                        else_.as_ref()
+                    },
+                };
+                if let Some(url) = branch {
+                    let schema = context.try_get(url)?;
+                    schema.validate(json)?
+                }
+                true
+            },
+            Condition::Items(..) | Condition::Properties(..) => {
+                for (child, json, url, is_additional) in self.children(json) {
+                    let schema = context.try_get(url)?;
+                    match (child, is_additional) {
+                        (Child::Key(k), true) => schema.validate(json).map_err(|err| ValidationError::AdditionalPropertyFailed {
+                            key: k.to_string(),
+                            cause: Box::new(err),
+                        })?,
+                        _ => schema.validate(json)?,
+                    }
+                }
+                true
+            },
+            Condition::Not(ref url) => {
+                let schema = context.try_get(url)?;
+                match schema.validate(json) {
+                    Ok(()) => false,
+                    Err(err) => if err.is_resolution_error() {
+                        return Err(err);
+                    } else {
+                        true
+                    },
+                }
+            },
+            Condition::OneOf(ref urls, ref discriminator) => {
+                if let Some(url) = discriminator.as_ref().and_then(|d| d.select(json)) {
+                    let schema = context.try_get(url)?;
+                    return schema.validate(json);
+                }
+                let mut errors = Vec::with_capacity(urls.len());
+                let mut passed = Vec::new();
+                for (index, url) in urls.iter().enumerate() {
+                    let schema = context.try_get(url)?;
+                    match schema.validate(json) {
+                        Ok(()) => passed.push(index),
+                        Err(err) => if err.is_resolution_error() {
+                            return Err(err);
+                        } else {
+                            errors.push(err);
+                        },
+                    }
+                }
+                match passed.len() {
+                    0 => return Err(ValidationError::OneOfAllFailed(errors)),
+                    1 => true,
+                    _ => return Err(ValidationError::OneOfManyPassed(passed)),
+                }
+            },
+            Condition::PropertyNames(ref url) => if let Value::Object(ref obj) = *json {
+                let schema = context.try_get(url)?;
+                for k in obj.keys() {
+                    schema.validate(&Value::String(k.clone()))?
+                }
+                true
+            } else {
+                true
+            },
+            Condition::Type(ref types) => types.iter().any(|t| t.type_of(json)),
+            _ => self.holds(json).expect("Condition with subschemas wasn't checked"),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(self.failure(json))
+        }
+    }
+
+    /// Returns the error for the value failing this condition. Numeric bounds
+    /// report the failing number; other conditions report just themselves.
+    fn failure(&self, json: &Value) -> ValidationError {
+        let (bound, kind) = match *self {
+            Condition::Maximum(ref n) => (n, BoundKind::Maximum),
+            Condition::ExclusiveMaximum(ref n) => (n, BoundKind::ExclusiveMaximum),
+            Condition::Minimum(ref n) => (n, BoundKind::Minimum),
+            Condition::ExclusiveMinimum(ref n) => (n, BoundKind::ExclusiveMinimum),
+            _ => return ValidationError::ConditionFailed(self.clone()),
+        };
+        if let Value::Number(ref actual) = *json {
+            ValidationError::NumberOutOfRange {
+                bound: bound.clone(),
+                actual: actual.clone(),
+                kind,
+            }
+        } else {
+            ValidationError::ConditionFailed(self.clone())
+        }
+    }
+
+    /// Returns whether the value passes the condition, without constructing
+    /// any errors, or `None` if `validate` would return a resolution error.
+    /// Each keyword checks its subschemas in the same order, and stops at the
+    /// same point, as `validate` does, so the two always agree.
+    pub(crate) fn check(&self, context: &Context, json: &Value) -> Option<bool> {
+        let check = |url: &Url, json: &Value| context.get(url).and_then(|schema| schema.check(json));
+        match (self, json) {
+            (&Condition::AllOf(ref urls, nothing), _) => {
+                let end = nothing.filter(|&i| is_nothing(context, &urls[i]))
+                    .map(|i| i + 1)
+                    .unwrap_or_else(|| urls.len());
+                check_all(urls[..end].iter().map(|url| check(url, json)))
+            },
+            (&Condition::AnyOf(ref urls), _) => {
+                let mut unresolved = false;
+                for url in urls {
+                    match context.get(url)?.check(json) {
+                        Some(true) => return Some(true),
+                        Some(false) => {},
+                        None => unresolved = true,
                     }
                 }
+                if unresolved { None } else { Some(false) }
+            },
+            (&Condition::Contains(ref url, min, max), &Value::Array(ref arr)) => {
+                let schema = context.get(url)?;
+                let min = min.unwrap_or(1);
+                let mut matches = 0;
+                let mut unresolved = false;
+                for v in arr {
+                    match schema.check(v) {
+                        Some(true) => {
+                            matches += 1;
+                            if max.is_none() && matches >= min {
+                                return Some(true);
+                            }
+                        },
+                        Some(false) => {},
+                        None => unresolved = true,
+                    }
+                }
+                if matches == 0 && min > 0 && unresolved {
+                    return None;
+                }
+                Some(matches >= min && max.map(|max| matches <= max).unwrap_or(true))
+            },
+            (&Condition::Dependencies(ref deps), &Value::Object(ref obj)) => check_all(deps.iter()
+                .filter(|&(k, _)| obj.contains_key(k))
+                .map(|(_, dep)| match *dep {
+                    Either::Left(ref props) => Some(props.iter().all(|p| obj.contains_key(p))),
+                    Either::Right(ref url) => check(url, json),
+                })),
+            #[cfg(feature = "draft7")]
+            (&Condition::If(ref cond, ref then, ref else_), _) => {
+                let branch = if check(cond, json)? { then } else { else_ };
+                branch.as_ref().map(|url| check(url, json)).unwrap_or(Some(true))
+            },
+            (&Condition::Items(..), _) | (&Condition::Properties(..), _) => {
+                check_all(self.children(json).map(|(_, json, url, _)| check(url, json)))
+            },
+            (&Condition::Not(ref url), _) => check(url, json).map(|valid| !valid),
+            (&Condition::OneOf(ref urls, ref discriminator), _) => {
+                if let Some(url) = discriminator.as_ref().and_then(|d| d.select(json)) {
+                    return check(url, json);
+                }
+                // Unlike with `anyOf`, every branch is checked, since a later
+                // one may not resolve.
+                let mut passed = 0;
+                for url in urls {
+                    if check(url, json)? {
+                        passed += 1;
+                    }
+                }
+                Some(passed == 1)
+            },
+            (&Condition::PropertyNames(ref url), &Value::Object(ref obj)) => {
+                let schema = context.get(url)?;
+                check_all(obj.keys().map(|k| schema.check(&Value::String(k.clone()))))
+            },
+            (&Condition::Type(ref types), _) => Some(types.iter().any(|t| t.type_of(json))),
+            _ => Some(self.holds(json).unwrap_or(true)),
+        }
+    }
+
+    /// Checks the value with the condition, if the condition doesn't have any
+    /// subschemas or otherwise depend on the Context. Returns `None` for those
+    /// that do.
+    fn holds(&self, json: &Value) -> Option<bool> {
+        let ok = match *self {
+            Condition::Const(ref v) => json_eq(json, v),
+            Condition::Enum(ref vs) => vs.iter().any(|v| json_eq(json, v)),
+            Condition::ExclusiveMaximum(ref m) => if let Value::Number(ref n) = *json {
+                number_cmp(n, m) == Some(Ordering::Less)
+            } else {
                 true
+            },
+            Condition::ExclusiveMinimum(ref m) => if let Value::Number(ref n) = *json {
+                number_cmp(n, m) == Some(Ordering::Greater)
+            } else {
+                true
+            },
+            Condition::MaxItems(n) => if let Value::Array(ref arr) = *json {
+                (arr.len() as u64) <= n
             } else {
                 true
             },
@@ -211,8 +931,18 @@ impl Condition {
             } else {
                 true
             },
+            Condition::MaxProperties(n) => if let Value::Object(ref obj) = *json {
+                (obj.len() as u64) <= n
+            } else {
+                true
+            },
             Condition::Maximum(ref m) => if let Value::Number(ref n) = *json {
-                n <= m
+                number_cmp(n, m) != Some(Ordering::Greater)
+            } else {
+                true
+            },
+            Condition::MinItems(n) => if let Value::Array(ref arr) = *json {
+                (arr.len() as u64) >= n
             } else {
                 true
             },
@@ -221,40 +951,23 @@ impl Condition {
             } else {
                 true
             },
-            Condition::Minimum(ref n) => if let Value::Number(ref num) = *json {
-                num >= n
+            Condition::MinProperties(n) => if let Value::Object(ref obj) = *json {
+                (obj.len() as u64) >= n
             } else {
                 true
             },
-            Condition::Pattern(RegexWrapper(ref re)) => if let Value::String(ref s) = *json {
-                re.is_match(s)
+            Condition::Minimum(ref n) => if let Value::Number(ref num) = *json {
+                number_cmp(num, n) != Some(Ordering::Less)
             } else {
                 true
             },
-            Condition::Properties(ref props, ref patterns, ref additional) => if let Value::Object(ref obj) = *json {
-                for (k, json) in obj {
-                    let mut is_additional = true;
-                    if let Some(url) = props.get(k) {
-                        is_additional = false;
-                        let schema = context.get(url)
-                            .ok_or_else(|| ValidationError::BadReference(url.clone()))?;
-                        schema.validate(json)?
-                    }
-                    for (_, url) in patterns.iter().filter(|&(re, _)| re.is_match(k)) {
-                        is_additional = false;
-                        let schema = context.get(url)
-                            .ok_or_else(|| ValidationError::BadReference(url.clone()))?;
-                        schema.validate(json)?
-                    }
-                    if is_additional {
-                        if let Some(url) = additional.as_ref() {
-                            let schema = context.get(url)
-                                .ok_or_else(|| ValidationError::BadReference(url.clone()))?;
-                            schema.validate(json)?
-                        }
-                    }
-                }
+            Condition::MultipleOf(ref m) => if let Value::Number(ref n) = *json {
+                is_multiple_of(n, m)
+            } else {
                 true
+            },
+            Condition::Pattern(RegexWrapper(ref re)) => if let Value::String(ref s) = *json {
+                re.is_match(s)
             } else {
                 true
             },
@@ -263,51 +976,141 @@ impl Condition {
             } else {
                 true
             },
-            Condition::Type(ref types) => types.iter().any(|t| t.type_of(json)),
-            _ => panic!("Condition {:?} not implemented", self),
+            Condition::UniqueItems(unique) => if let (true, &Value::Array(ref arr)) = (unique, json) {
+                let mut seen = HashSet::with_capacity(arr.len());
+                arr.iter().all(|v| seen.insert(CanonicalValue(v)))
+            } else {
+                true
+            },
+            _ => return None,
         };
-        if ok {
-            Ok(())
-        } else {
-            Err(ValidationError::ConditionFailed(self.clone()))
+        Some(ok)
+    }
+}
+
+/// Numbers and JSON values are hashed by value, the way `const` and `enum`
+/// compare them, so for example `Maximum(1)` and `Maximum(1.0)` hash the same
+/// even though they aren't equal.
+impl Hash for Condition {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        discriminant(self).hash(state);
+        match *self {
+            Condition::MultipleOf(ref n) |
+            Condition::Maximum(ref n) |
+            Condition::ExclusiveMaximum(ref n) |
+            Condition::Minimum(ref n) |
+            Condition::ExclusiveMinimum(ref n) => canonical_number(n).hash(state),
+            Condition::MaxLength(n) |
+            Condition::MinLength(n) |
+            Condition::MaxItems(n) |
+            Condition::MinItems(n) |
+            Condition::MaxProperties(n) |
+            Condition::MinProperties(n) => n.hash(state),
+            Condition::Pattern(ref re) => re.hash(state),
+            Condition::Items(ref items, ref additional) => {
+                items.hash(state);
+                additional.hash(state);
+            },
+            Condition::UniqueItems(b) => b.hash(state),
+            Condition::Contains(ref url, min, max) => {
+                url.hash(state);
+                min.hash(state);
+                max.hash(state);
+            },
+            Condition::PropertyNames(ref url) |
+            Condition::Not(ref url) => url.hash(state),
+            Condition::Required(ref names) => names.hash(state),
+            Condition::Properties(ref props, ref patterns, ref additional) => {
+                props.hash(state);
+                patterns.hash(state);
+                additional.hash(state);
+            },
+            Condition::Dependencies(ref deps) => deps.hash(state),
+            Condition::Enum(ref values) => {
+                values.len().hash(state);
+                for v in values {
+                    CanonicalValue(v).hash(state);
+                }
+            },
+            Condition::Const(ref value) => CanonicalValue(value).hash(state),
+            Condition::Type(ref types) => types.hash(state),
+            Condition::AllOf(ref urls, _) |
+            Condition::AnyOf(ref urls) => urls.hash(state),
+            Condition::OneOf(ref urls, ref discriminator) => {
+                urls.hash(state);
+                discriminator.hash(state);
+            },
+            #[cfg(feature = "draft7")]
+            Condition::If(ref cond, ref then, ref else_) => {
+                cond.hash(state);
+                then.hash(state);
+                else_.hash(state);
+            },
         }
     }
 }
 
-/// The type of a JSON value.
+// `PartialEq` is total: the only payloads that could make it otherwise are
+// numbers, and a JSON `Number` can't be NaN.
+impl Eq for Condition {}
+
+/// An OpenAPI-style `discriminator` on a `oneOf`: a property whose value
+/// picks the schema an object should match, so that only that schema needs to
+/// be checked.
 ///
-/// Under this definition of type, a value may have more than one type. For
-/// example, `4` has both the type `Integer` and the type `Number`.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum Type {
-    /// The type of the `null` value.
-    Null,
-    /// The type of `true` and `false`.
-    Boolean,
-    /// The type of all numbers.
-    Number,
-    /// The type of all integers (between -2^63 and 2^64).
-    Integer,
-    /// The type of all strings.
-    String,
-    /// The type of all arrays.
-    Array,
-    /// The type of all objects.
-    Object,
+/// This is an extension keyword, not part of JSON Schema, and only has an
+/// effect on schemas that use it alongside `oneOf`; without one, it's treated
+/// like any other unknown keyword. If the object doesn't have
+/// the property, or its value isn't a string in the mapping, the `oneOf` is
+/// checked as usual. Otherwise, only the mapped schema is checked, and its
+/// errors are returned as they are; the other `oneOf` branches aren't tried,
+/// so it's up to the schema's author to keep the mapping consistent with them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Discriminator {
+    /// The name of the property holding the discriminating value.
+    pub property_name: String,
+
+    /// The URI of the schema to use for each value of the property.
+    pub mapping: BTreeMap<String, Url>,
+}
+
+impl Discriminator {
+    /// Returns the URI of the schema the discriminating property selects for
+    /// the value, if any.
+    pub fn select(&self, json: &Value) -> Option<&Url> {
+        json.get(&self.property_name)
+            .and_then(|v| v.as_str())
+            .and_then(|v| self.mapping.get(v))
+    }
+
+    fn to_value(&self) -> Value {
+        let mut obj = Map::new();
+        obj.insert("propertyName".to_string(), Value::String(self.property_name.clone()));
+        obj.insert("mapping".to_string(), Value::Object(self.mapping.iter()
+            .map(|(k, url)| (k.to_string(), Value::String(url.to_string())))
+            .collect()));
+        Value::Object(obj)
+    }
 }
 
 impl Type {
-    /// Tries to convert the string to a Type, returning None if it does not
-    /// match.
-    pub fn from_string(s: &str) -> Option<Type> {
-        match s {
-            "null" => Some(Type::Null),
-            "boolean" => Some(Type::Boolean),
-            "number" => Some(Type::Number),
-            "integer" => Some(Type::Integer),
-            "string" => Some(Type::String),
-            "array" => Some(Type::Array),
-            "object" => Some(Type::Object),
+    /// Tries to convert the string to a value of this type. Only booleans,
+    /// integers, and numbers can be converted to.
+    fn coerce(&self, s: &str) -> Option<Value> {
+        match *self {
+            Type::Boolean => match s {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            Type::Integer => s.parse::<u64>().map(Value::from)
+                .or_else(|_| s.parse::<i64>().map(Value::from))
+                .ok(),
+            Type::Number => Type::Integer.coerce(s).or_else(|| {
+                s.parse::<f64>().ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number)
+            }),
             _ => None,
         }
     }
@@ -318,7 +1121,7 @@ impl Type {
             (&Type::Null, &Value::Null) => true,
             (&Type::Boolean, &Value::Bool(_)) => true,
             (&Type::Number, &Value::Number(_)) => true,
-            (&Type::Integer, &Value::Number(ref n)) => n.is_u64() || n.is_i64(),
+            (&Type::Integer, &Value::Number(ref n)) => number_is_integral(n),
             (&Type::String, &Value::String(_)) => true,
             (&Type::Array, &Value::Array(_)) => true,
             (&Type::Object, &Value::Object(_)) => true,
@@ -327,6 +1130,227 @@ impl Type {
     }
 }
 
+impl Display for Condition {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        write!(fmt, "{} ", self.keyword())?;
+        match *self {
+            Condition::MultipleOf(ref n) => write!(fmt, "{}", n),
+            Condition::Maximum(ref n) => write!(fmt, "{}", n),
+            Condition::ExclusiveMaximum(ref n) => write!(fmt, "{}", n),
+            Condition::Minimum(ref n) => write!(fmt, "{}", n),
+            Condition::ExclusiveMinimum(ref n) => write!(fmt, "{}", n),
+            Condition::MaxLength(n) => write!(fmt, "{}", n),
+            Condition::MinLength(n) => write!(fmt, "{}", n),
+            Condition::Pattern(ref re) => write!(fmt, "{:?}", re.as_str()),
+            Condition::Items(ref items, ref additional) => if items.is_empty() {
+                match *additional {
+                    Some(ref url) => write!(fmt, "{}", url),
+                    None => write!(fmt, "[]"),
+                }
+            } else {
+                write_list(fmt, items)?;
+                match *additional {
+                    Some(ref url) => write!(fmt, ", additionalItems {}", url),
+                    None => Ok(()),
+                }
+            },
+            Condition::MaxItems(n) => write!(fmt, "{}", n),
+            Condition::MinItems(n) => write!(fmt, "{}", n),
+            Condition::UniqueItems(b) => write!(fmt, "{}", b),
+            Condition::Contains(ref url, min, max) => {
+                write!(fmt, "{}", url)?;
+                if let Some(min) = min {
+                    write!(fmt, ", minContains {}", min)?;
+                }
+                match max {
+                    Some(max) => write!(fmt, ", maxContains {}", max),
+                    None => Ok(()),
+                }
+            },
+            Condition::MaxProperties(n) => write!(fmt, "{}", n),
+            Condition::MinProperties(n) => write!(fmt, "{}", n),
+            Condition::Required(ref props) => write_list(fmt, props),
+            Condition::Properties(ref props, ref patterns, ref additional) => {
+                write_list(fmt, props.keys())?;
+                if !patterns.is_empty() {
+                    write!(fmt, ", patternProperties ")?;
+                    write_list(fmt, patterns.keys().map(|re| re.as_str()))?;
+                }
+                match *additional {
+                    Some(ref url) => write!(fmt, ", additionalProperties {}", url),
+                    None => Ok(()),
+                }
+            },
+            Condition::Dependencies(ref deps) => write_list(fmt, deps.keys()),
+            Condition::PropertyNames(ref url) => write!(fmt, "{}", url),
+            Condition::Enum(ref values) => write_list(fmt, values),
+            Condition::Const(ref value) => write!(fmt, "{}", value),
+            Condition::Type(ref types) => if types.len() == 1 {
+                write!(fmt, "{}", types[0])
+            } else {
+                write_list(fmt, types)
+            },
+            Condition::AllOf(ref urls, _) => write_list(fmt, urls),
+            Condition::AnyOf(ref urls) => write_list(fmt, urls),
+            Condition::OneOf(ref urls, _) => write_list(fmt, urls),
+            Condition::Not(ref url) => write!(fmt, "{}", url),
+            #[cfg(feature = "draft7")]
+            Condition::If(ref cond, ref then, ref else_) => {
+                write!(fmt, "{}", cond)?;
+                if let Some(ref then) = *then {
+                    write!(fmt, ", then {}", then)?;
+                }
+                if let Some(ref else_) = *else_ {
+                    write!(fmt, ", else {}", else_)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Returns the index of the first of the schemas that is the `false` schema,
+/// or a `$ref` to it, if any.
+pub(crate) fn nothing_branch(context: &Context, urls: &[Url]) -> Option<usize> {
+    urls.iter().position(|url| is_nothing(context, url))
+}
+
+/// Returns whether the schema with the given URI is the `false` schema, or a
+/// `$ref` to it.
+fn is_nothing(context: &Context, url: &Url) -> bool {
+    let mut seen = Vec::new();
+    let mut url = url.clone();
+    while let Some(schema) = context.get(&url) {
+        match schema.inner.validator {
+            Validator::Nothing => return true,
+            Validator::Reference(ref r) if !seen.contains(r) => {
+                seen.push(url);
+                url = r.clone();
+            },
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Returns the JSON value of the subschema with the given URI, as written
+/// inside the schema with the URI `parent`. A dangling URI is written as a
+/// `$ref`.
+fn subschema_value(context: &Context, parent: &Url, url: &Url) -> Value {
+    match context.get(url) {
+        Some(schema) => schema.to_value_in(parent),
+        None => {
+            let mut obj = Map::new();
+            obj.insert("$ref".to_string(), Value::String(url.to_string()));
+            Value::Object(obj)
+        },
+    }
+}
+
+/// Adds the errors from validating the value with the schema with the given
+/// URI to the tree.
+fn validate_tree_with(context: &Context, url: &Url, json: &Value, tree: &mut ErrorTree) {
+    if let Some(schema) = context.get(url) {
+        schema.inner.validator.validate_tree(context, json, tree);
+    } else {
+        tree.push(ValidationError::BadReference(url.clone()));
+    }
+}
+
+/// Passes each error from validating the value with the schema with the given
+/// URI to the sink, after wrapping it with `wrap`. A missing schema is passed
+/// as a `BadReference` error as it is, like `Condition::validate` does.
+fn validate_each_with(context: &Context, url: &Url, json: &Value, sink: &mut dyn FnMut(ValidationError) -> bool,
+        wrap: &dyn Fn(ValidationError) -> ValidationError) -> bool {
+    if let Some(schema) = context.get(url) {
+        schema.inner.validator.validate_each(context, json, &mut |err| sink(wrap(err)))
+    } else {
+        sink(ValidationError::BadReference(url.clone()))
+    }
+}
+
+/// Adds the annotations from the schema with the given URI, if it exists.
+fn annotate_with(context: &Context, url: &Url, json: &Value, path: &mut Vec<String>, out: &mut Annotations) {
+    if let Some(schema) = context.get(url) {
+        schema.annotate(json, path, out);
+    }
+}
+
+/// Coerces the value with the schema with the given URI, if it exists.
+fn coerce_with(context: &Context, url: &Url, json: &mut Value) {
+    if let Some(schema) = context.get(url) {
+        schema.inner.validator.coerce(context, json);
+    }
+}
+
+/// Coerces the value to the first of the given types that it can be coerced
+/// to, returning the coerced value. Only strings are coerced.
+fn coerce_to(types: &[Type], json: &Value) -> Option<Value> {
+    if let Value::String(ref s) = *json {
+        types.iter().filter_map(|ty| ty.coerce(s)).next()
+    } else {
+        None
+    }
+}
+
+/// Returns the child of an array or object value with the given index or
+/// property name, as written by `Child`'s `Display` impl.
+fn child_mut<'a>(json: &'a mut Value, child: &str) -> Option<&'a mut Value> {
+    match *json {
+        Value::Array(ref mut arr) => child.parse::<usize>().ok().and_then(move |i| arr.get_mut(i)),
+        Value::Object(ref mut obj) => obj.get_mut(child),
+        _ => None,
+    }
+}
+
+/// A child of an array or object value, as returned by `Condition::children`.
+#[derive(Clone, Copy, Debug)]
+enum Child<'a> {
+    /// An element of an array, by its index.
+    Index(usize),
+    /// A property of an object, by its name.
+    Key(&'a str),
+}
+
+impl<'a> Display for Child<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match *self {
+            Child::Index(i) => write!(fmt, "{}", i),
+            Child::Key(k) => fmt.write_str(k),
+        }
+    }
+}
+
+/// Returns whether `n` is a multiple of `m`. Integers are checked exactly;
+/// otherwise, `n` must be within a few ulps of `m` times the nearest integer
+/// to `n / m`. The allowed error is relative to `n` rather than to the
+/// quotient, so it doesn't grow large enough to let every number pass when
+/// `m` is small.
+fn is_multiple_of(n: &Number, m: &Number) -> bool {
+    if let (Some(n), Some(m)) = (n.as_u64(), m.as_u64()) {
+        n % m == 0
+    } else if let (Some(n), Some(m)) = (n.as_i64(), m.as_i64()) {
+        n % m == 0
+    } else {
+        let n = n.as_f64().expect("Number wasn't f64");
+        let m = m.as_f64().expect("Number wasn't f64");
+        let q = (n / m).round();
+        q.is_finite() && (n - m * q).abs() <= n.abs() * ::std::f64::EPSILON * 4.0
+    }
+}
+
+/// Writes the items as a comma-separated list in square brackets.
+fn write_list<I: IntoIterator<Item=T>, T: Display>(fmt: &mut Formatter, items: I) -> FmtResult {
+    write!(fmt, "[")?;
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(fmt, ", ")?;
+        }
+        write!(fmt, "{}", item)?;
+    }
+    write!(fmt, "]")
+}
+
 #[derive(Clone, Debug)]
 pub struct RegexWrapper(pub Regex);
 
@@ -339,7 +1363,13 @@ impl Eq for RegexWrapper {}
 
 impl Ord for RegexWrapper {
     fn cmp(&self, other: &RegexWrapper) -> Ordering {
-        unimplemented!()
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for RegexWrapper {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
     }
 }
 
@@ -1,52 +1,758 @@
-use errors::FromValueError;
-use serde_json::Value;
-use std::collections::BTreeMap;
-use super::{JsonSchema, JsonSchemaInner, METASCHEMA_URI};
+use errors::{FromValueError, ValidationError};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufRead;
+use std::mem::{self, size_of};
+use std::sync::Arc;
+use stream::ValidateNdjson;
+use super::{same_document, Condition, FrozenContext, JsonSchema, JsonSchemaInner, SchemaId, Type, Validator};
+use super::condition::nothing_branch;
+use super::size::url_heap_size;
+#[cfg(feature = "metaschema")]
+use super::{METASCHEMA_URI, METASCHEMA_VALUE};
 use url::Url;
 
 /// The context a JSON Schema is created and run in.
-#[derive(Clone, Debug, Default, PartialEq)]
+///
+/// The schemas are held behind an `Arc`, so cloning a Context is cheap, and
+/// clones can be handed to threads that only validate. Creating or replacing
+/// a schema in a clone whose schemas are still shared copies them first
+/// (with `Arc::make_mut`), so it doesn't affect the other clones; it costs a
+/// deep copy of every schema, though, so load all the schemas before sharing
+/// the Context.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Context {
-    schemas: BTreeMap<Url, JsonSchemaInner>,
+    anchors: Arc<BTreeMap<Url, Url>>,
+    /// The anchor URI of each schema that has one, by the URI the schema is
+    /// stored under; the reverse of `anchors`.
+    anchor_names: Arc<BTreeMap<Url, Url>>,
+    /// The anchors found by the current call to `parse_document`, which are
+    /// only added to `anchors` once the whole document has parsed.
+    pending_anchors: BTreeMap<Url, Url>,
+    dedup: Arc<Dedup>,
+    schemas: Arc<BTreeMap<Url, JsonSchemaInner>>,
+    /// The Context to look a schema up in when it isn't in this one. This is
+    /// only set for the scratch Context of `validate_transient`.
+    fallback: Option<Arc<Context>>,
+    pub(crate) coerce_types: bool,
+    pub(crate) dedup_subschemas: bool,
+    pub(crate) draft4_exclusive_bounds: bool,
+    pub(crate) strict_keywords: bool,
+}
+
+/// What's recorded to deduplicate subschemas; see
+/// `Context::set_dedup_subschemas`.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Dedup {
+    /// The URIs of subschemas that weren't stored, mapped to the URI of the
+    /// identical schema that's used instead.
+    aliases: BTreeMap<Url, Url>,
+    /// The URI of each schema that can be reused, by its document and source.
+    /// This only lasts for one call to `parse`, so it can't go stale.
+    index: BTreeMap<(Url, String), Url>,
 }
 
 impl Context {
-    /// Creates a new Context.
+    /// Creates a new Context. With the `metaschema` feature, the draft-06
+    /// metaschema is added to it, and like any other schema its subschemas
+    /// can be referred to by JSON pointer, so schemas can `$ref` e.g.
+    /// `http://json-schema.org/draft-06/schema#/definitions/stringArray`.
     pub fn new() -> Context {
         // Create the context.
-        let ctx = Context { schemas: BTreeMap::new() };
-        
+        #[allow(unused_mut)]
+        let mut ctx = Context::empty();
+
         // Add the metaschema to the context.
-        ctx.make_schema(*METASCHEMA_URI, *METASCHEMA_VALUE)
+        #[cfg(feature = "metaschema")]
+        ctx.make_schema_unchecked(METASCHEMA_URI.clone(), &METASCHEMA_VALUE)
             .expect("Couldn't build the metaschema?");
 
         // Return the context.
         ctx
     }
 
-    /// Creates a JsonSchema from a JSON value.
-    pub fn make_schema<'a>(&'a mut self, base_uri: Url, json: &Value) -> Result<JsonSchema<'a>, FromValueError> {
-        let uri = self.parse(base_uri, json, 0)?;
+    /// Creates a Context with no schemas, not even the metaschema, and with
+    /// every setting off.
+    pub(crate) fn empty() -> Context {
+        Context {
+            anchors: Arc::new(BTreeMap::new()),
+            anchor_names: Arc::new(BTreeMap::new()),
+            pending_anchors: BTreeMap::new(),
+            dedup: Arc::new(Dedup::default()),
+            schemas: Arc::new(BTreeMap::new()),
+            fallback: None,
+            coerce_types: false,
+            dedup_subschemas: false,
+            draft4_exclusive_bounds: false,
+            strict_keywords: false,
+        }
+    }
+
+    /// Creates an empty Context with the same settings as this one, to parse
+    /// a schema into without changing this one.
+    fn scratch(&self) -> Context {
+        Context {
+            coerce_types: self.coerce_types,
+            dedup_subschemas: self.dedup_subschemas,
+            draft4_exclusive_bounds: self.draft4_exclusive_bounds,
+            strict_keywords: self.strict_keywords,
+            ..Context::empty()
+        }
+    }
+
+    /// Creates a JsonSchema from a JSON value. The base URI can be given as a
+    /// [`SchemaId`](struct.SchemaId.html) or as a `Url`.
+    ///
+    /// If the value has an `$id`, it's resolved against the base URI, and the
+    /// schema is stored under the result instead; so a schema with `"$id":
+    /// "https://example.com/person"` can later be validated against with
+    /// that URI, whatever base URI it was loaded with.
+    ///
+    /// With the `metaschema` feature, the value is first validated against
+    /// the metaschema, and a `MetaschemaFailedToValidate` error is returned if
+    /// it doesn't pass.
+    pub fn make_schema<'a, I: Into<SchemaId>>(&'a mut self, base_uri: I, json: &Value) -> Result<JsonSchema<'a>, FromValueError> {
+        #[cfg(feature = "metaschema")]
+        self.validate(&METASCHEMA_URI, json)
+            .map_err(FromValueError::MetaschemaFailedToValidate)?;
+        self.make_schema_unchecked(base_uri, json)
+    }
+
+    /// Creates a JsonSchema from a JSON value, without validating it against
+    /// the metaschema. Without the `metaschema` feature, this is the same as
+    /// `make_schema`.
+    ///
+    /// Validating against the metaschema makes up much of the cost of loading
+    /// a schema, so this is meant for schemas that are already known to be
+    /// valid, e.g. ones checked when the program was built. The parser still
+    /// rejects keywords with the wrong type, and counts (like `minLength`)
+    /// that are negative or fractional, but a schema that is invalid in other
+    /// ways (for example, a `required` with duplicate names) may be accepted
+    /// and then behave in unspecified ways when validating. Don't use this
+    /// for schemas from untrusted sources.
+    pub fn make_schema_unchecked<'a, I: Into<SchemaId>>(&'a mut self, base_uri: I, json: &Value) -> Result<JsonSchema<'a>, FromValueError> {
+        let uri = self.parse_document(base_uri.into().into_url(), json, 0)?;
         Ok(self.get(&uri).unwrap())
     }
 
+    /// Creates a JsonSchema from a JSON value that has no URI of its own, and
+    /// returns the URI it was given, which comes from
+    /// [`SchemaId::anonymous`](struct.SchemaId.html#method.anonymous).
+    ///
+    /// Since the URI is a URN, relative `$ref`s in the schema can't be
+    /// resolved; references should be absolute URIs or fragments.
+    pub fn make_anonymous_schema(&mut self, json: &Value) -> Result<Url, FromValueError> {
+        let uri = SchemaId::anonymous().into_url();
+        self.make_schema(uri.clone(), json)?;
+        Ok(uri)
+    }
+
+    /// Creates a schema that matches values which match every one of the
+    /// schemas with the given URIs, as with `allOf`, and returns its URI. The
+    /// URI comes from
+    /// [`SchemaId::anonymous`](struct.SchemaId.html#method.anonymous).
+    ///
+    /// Like a `$ref`, the URIs are resolved when validating, so they don't
+    /// need to be in the Context yet. At least one URI must be given.
+    pub fn compose_all_of(&mut self, parts: &[Url]) -> Result<Url, FromValueError> {
+        if parts.is_empty() {
+            let mut obj = Map::new();
+            obj.insert("allOf".to_string(), Value::Array(Vec::new()));
+            return Err(FromValueError::InvalidKeywordValue(Value::Object(obj), "allOf".to_string(), Value::Array(Vec::new())));
+        }
+
+        let uri = SchemaId::anonymous().into_url();
+        self.put(uri.clone(), JsonSchemaInner {
+            annotations: Map::new(),
+            description: None,
+            diagnostics: Vec::new(),
+            format: None,
+            title: None,
+            validator: Validator::Conditions(vec![Condition::AllOf(parts.to_vec(), nothing_branch(self, parts))]),
+        });
+        Ok(uri)
+    }
+
+    /// Validates a JSON value against a schema given as a JSON value, without
+    /// adding the schema to the Context. The schema can still `$ref` the
+    /// schemas that are in the Context. If the schema itself is invalid, an
+    /// `InvalidSchema` error is returned.
+    ///
+    /// This is meant for one-off validation, where the schema differs every
+    /// time. It's much slower than validating against a schema made with
+    /// `make_schema`: every call parses (and, with the `metaschema` feature,
+    /// checks) the schema, which usually costs more than validating the value
+    /// does. The schemas already in the Context aren't copied, though.
+    pub fn validate_transient(&self, schema: &Value, json: &Value) -> Result<(), ValidationError> {
+        // The schema is parsed into an empty Context, which looks up the
+        // schemas it doesn't have in this one.
+        let mut scratch = self.scratch();
+        scratch.fallback = Some(Arc::new(self.clone()));
+        let uri = SchemaId::anonymous();
+        let schema = scratch.make_schema(uri, schema)
+            .map_err(|err| ValidationError::InvalidSchema(Box::new(err)))?;
+        schema.validate(json)
+    }
+
+    /// Returns the number of schemas in the Context, counting subschemas and
+    /// (with the `metaschema` feature) the metaschema.
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Returns whether there are no schemas in the Context.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// Returns a rough estimate of how many bytes the schemas in the Context
+    /// take up, for deciding how many to keep around.
+    ///
+    /// This counts the schemas and the memory they own, but not allocator
+    /// overhead or unused capacity. Compiled regexes don't expose their size,
+    /// so each is guessed from the length of its source.
+    pub fn estimated_size(&self) -> usize {
+        let schemas = self.schemas.iter()
+            .map(|(uri, inner)| size_of::<Url>() + url_heap_size(uri) + inner.estimated_size())
+            .sum::<usize>();
+        let anchors = self.anchors.iter().chain(self.anchor_names.iter()).chain(&self.dedup.aliases)
+            .map(|(anchor, uri)| 2 * size_of::<Url>() + url_heap_size(anchor) + url_heap_size(uri))
+            .sum::<usize>();
+        size_of::<Context>() + size_of::<Dedup>() + schemas + anchors
+    }
+
     /// Gets a JsonSchema from the Context.
+    ///
+    /// If the URI's fragment is a plain name (e.g. `#foo`), it refers to the
+    /// schema whose `$id` is that anchor. Otherwise, the fragment is a JSON
+    /// pointer to a schema in the document, and an empty fragment refers to
+    /// the document itself.
+    ///
+    /// A `&SchemaId` can be passed as well, since it dereferences to a `Url`.
     pub fn get<'a>(&'a self, uri: &Url) -> Option<JsonSchema<'a>> {
-        if *uri == *METASCHEMA_URI {
-            unimplemented!()
-        } else {
-            self.schemas.get(uri).map(|inner| {
-                JsonSchema {
-                    ctx: self,
-                    id: uri.clone(),
-                    inner: inner,
+        self.find(uri).map(|(uri, inner)| {
+            JsonSchema {
+                ctx: self,
+                id: uri.clone(),
+                inner: inner,
+            }
+        })
+    }
+
+    /// Gets a JsonSchema from the Context, like `get`, but returns a
+    /// `BadReference` error naming the URI if there is no such schema.
+    pub fn try_get<'a>(&'a self, uri: &Url) -> Result<JsonSchema<'a>, ValidationError> {
+        self.get(uri).ok_or_else(|| ValidationError::BadReference(uri.clone()))
+    }
+
+    /// Gets the JsonSchema with the given plain-name anchor (without the `#`)
+    /// in the document with the given URI. For example, `get_by_anchor(base,
+    /// "User")` finds the schema with `"$id": "#User"` under `base`.
+    ///
+    /// Returns `None` if no such anchor was registered, or if `anchor` is
+    /// empty or a JSON pointer; use `get` for those.
+    pub fn get_by_anchor<'a>(&'a self, base: &Url, anchor: &str) -> Option<JsonSchema<'a>> {
+        if anchor.is_empty() || anchor.starts_with('/') {
+            return None;
+        }
+        let mut uri = base.clone();
+        uri.set_fragment(Some(anchor));
+        self.anchors.get(&uri).and_then(|uri| self.get(uri))
+    }
+
+    /// Returns the JSON value of the schema a URI refers to, as `get` finds
+    /// it. This is for showing what a `$ref` points at, e.g. when debugging.
+    ///
+    /// The original source isn't kept, so this is the schema written back out
+    /// with [`JsonSchema::to_value`](struct.JsonSchema.html#method.to_value):
+    /// keywords are in their parsed form, and the ones listed under
+    /// [`JsonSchema::to_value_full`](struct.JsonSchema.html#method.to_value_full)
+    /// are missing.
+    pub fn resolve(&self, uri: &Url) -> Option<Value> {
+        self.get(uri).map(|schema| schema.to_value())
+    }
+
+    /// Finds the schema a URI refers to, along with the URI it's stored
+    /// under. See `get` for how the URI is resolved.
+    fn find(&self, uri: &Url) -> Option<(&Url, &JsonSchemaInner)> {
+        self.find_here(uri).or_else(|| {
+            self.fallback.as_ref().and_then(|ctx| ctx.find(uri))
+        })
+    }
+
+    /// Finds the schema a URI refers to, like `find`, without looking in the
+    /// fallback Context.
+    fn find_here(&self, uri: &Url) -> Option<(&Url, &JsonSchemaInner)> {
+        match uri.fragment() {
+            Some(f) if !f.is_empty() && !f.starts_with('/') => {
+                self.anchors.get(uri)
+                    .and_then(|uri| self.schemas.get_key_value(uri))
+            },
+            Some(_) | None => self.schemas.get_key_value(uri).or_else(|| {
+                // `doc` and `doc#` refer to the same schema.
+                match uri.fragment() {
+                    Some("") => {
+                        let mut uri = uri.clone();
+                        uri.set_fragment(None);
+                        self.schemas.get_key_value(&uri)
+                    },
+                    None => {
+                        let mut uri = uri.clone();
+                        uri.set_fragment(Some(""));
+                        self.schemas.get_key_value(&uri)
+                    },
+                    Some(_) => None,
                 }
+            }).or_else(|| {
+                // A deduplicated subschema is found under its own URI, but
+                // with the schema it was identical to.
+                self.dedup.aliases.get_key_value(uri)
+                    .and_then(|(uri, target)| self.schemas.get(target).map(|inner| (uri, inner)))
+            }),
+        }
+    }
+
+    /// Replaces the schema with the given URI, and the subschemas written
+    /// inside it, with one created from the given JSON value. Other schemas
+    /// are left alone, and since references are resolved when validating,
+    /// schemas that `$ref` the replaced one see the new version. If there was
+    /// no schema with the URI, this is the same as `make_schema`.
+    ///
+    /// The URI may point into a document, e.g. `schema.json#/definitions/x`,
+    /// in which case the new schema is parsed as a subschema. If the schema is
+    /// invalid, the Context is left unchanged.
+    ///
+    /// Subschemas of the old schema that were given an `$id` outside of it
+    /// aren't removed.
+    pub fn replace(&mut self, uri: Url, json: &Value) -> Result<(), FromValueError> {
+        #[cfg(feature = "metaschema")]
+        self.validate(&METASCHEMA_URI, json)
+            .map_err(FromValueError::MetaschemaFailedToValidate)?;
+
+        let uri = self.anchors.get(&uri).cloned().unwrap_or(uri);
+        let depth = match uri.fragment() {
+            Some(f) if !f.is_empty() => 1,
+            _ => 0,
+        };
+        let mut scratch = self.scratch();
+        scratch.parse_document(uri.clone(), json, depth)?;
+
+        // Subschemas elsewhere that were deduplicated into the replaced schema
+        // get their own copy of the old version, so they don't change too.
+        let orphans = self.dedup.aliases.iter()
+            .filter(|&(k, v)| !is_within(k, &uri) && is_within(v, &uri))
+            .filter_map(|(k, v)| self.schemas.get(v).map(|inner| (k.clone(), inner.clone())))
+            .collect::<Vec<_>>();
+
+        let schemas = Arc::make_mut(&mut self.schemas);
+        retain(schemas, |k, _| !is_within(k, &uri));
+        schemas.extend(orphans);
+        schemas.extend(unwrap_or_clone(scratch.schemas));
+        self.remove_anchors(|target| is_within(target, &uri));
+        self.add_anchors(unwrap_or_clone(scratch.anchors));
+        let dedup = Arc::make_mut(&mut self.dedup);
+        let scratch_dedup = unwrap_or_clone(scratch.dedup);
+        retain(&mut dedup.aliases, |k, v| !is_within(k, &uri) && !is_within(v, &uri));
+        dedup.aliases.extend(scratch_dedup.aliases);
+        Ok(())
+    }
+
+    /// Creates schemas from a bundle, which is a JSON object mapping URIs to
+    /// the schemas to create under them, as returned by `to_bundle`.
+    pub fn load_bundle(&mut self, bundle: &Value) -> Result<(), FromValueError> {
+        if let Value::Object(ref obj) = *bundle {
+            for (uri, schema) in obj {
+                let uri = Url::parse(uri).map_err(|e| {
+                    FromValueError::InvalidId(schema.clone(), uri.to_owned(), e)
+                })?;
+                self.make_schema(uri, schema)?;
+            }
+            Ok(())
+        } else {
+            Err(FromValueError::InvalidSchemaType(bundle.clone()))
+        }
+    }
+
+    /// Returns a bundle of every schema in the Context, which is a JSON object
+    /// mapping each schema's URI to its JSON value. Loading the bundle into an
+    /// empty Context with `load_bundle` gives an equivalent Context, so this
+    /// can be used to persist or compare a set of schemas.
+    ///
+    /// Subschemas are written out in full under each schema that contains
+    /// them, as well as under their own URIs, so a bundle can be much larger
+    /// than the schemas that were loaded to produce it.
+    pub fn to_bundle(&self) -> Value {
+        Value::Object(self.schemas.keys()
+            .filter_map(|uri| self.get(uri))
+            .map(|schema| (schema.id.to_string(), schema.to_document_value()))
+            .collect())
+    }
+
+    /// Checks that every reference made by a schema in the Context (by `$ref`
+    /// or by a subschema) can be resolved, returning the ones that can't.
+    ///
+    /// Since references are resolved lazily, a dangling reference otherwise
+    /// won't be noticed until a value is validated against it. Calling this
+    /// once all schemas are loaded lets a program fail fast instead.
+    pub fn validate_references(&self) -> Result<(), Vec<Url>> {
+        let unresolved = self.unresolved_refs();
+        if unresolved.is_empty() {
+            Ok(())
+        } else {
+            Err(unresolved)
+        }
+    }
+
+    /// Checks that every reference resolves, as `validate_references` does,
+    /// and if so returns the Context in a form that can't be changed, so
+    /// they're guaranteed to keep resolving. See
+    /// [`FrozenContext`](struct.FrozenContext.html).
+    ///
+    /// The references that can't be resolved are returned otherwise.
+    pub fn freeze(self) -> Result<FrozenContext, Vec<Url>> {
+        self.validate_references()?;
+        Ok(FrozenContext::new(self))
+    }
+
+    /// Returns the references that can't be resolved, in sorted order and
+    /// without duplicates.
+    pub(crate) fn unresolved_refs(&self) -> Vec<Url> {
+        self.schemas.values()
+            .flat_map(|inner| inner.validator.subschemas())
+            .filter(|uri| self.find(uri).is_none())
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Validates a JSON value using the schema with the given URI. If there is
+    /// no such schema in the Context, returns a `BadReference` error.
+    pub fn validate(&self, schema_uri: &Url, json: &Value) -> Result<(), ValidationError> {
+        self.try_get(schema_uri)?.validate(json)
+    }
+
+    /// Validates newline-delimited JSON read from the given reader against the
+    /// schema with the given URI, yielding each result with its line number.
+    /// See [`ValidateNdjson`](struct.ValidateNdjson.html) for details.
+    ///
+    /// If there's no schema with the URI, every line yields a `BadReference`
+    /// error, as with `validate`.
+    pub fn validate_ndjson<'a, R: BufRead>(&'a self, schema_uri: &Url, reader: R) -> ValidateNdjson<'a, R> {
+        ValidateNdjson::new(self, schema_uri.clone(), reader)
+    }
+
+    /// Sets whether strings are coerced to the types a schema asks for, which
+    /// is useful for data from forms or query strings, where everything is a
+    /// string. By default, no coercion is done.
+    ///
+    /// When this is on,
+    /// [`JsonSchema::validate_coerce`](struct.JsonSchema.html#method.validate_coerce)
+    /// coerces a string that fails a `type` check to the first of the types
+    /// that it can be: `"5"` to an integer or number, `"2.5"` to a number, and
+    /// `"true"` or `"false"` to a boolean. It returns the coerced value, and
+    /// checks the whole schema against it. Other validation methods don't
+    /// coerce, so they treat such a string like any other that fails `type`.
+    pub fn set_coerce_types(&mut self, coerce: bool) {
+        self.coerce_types = coerce;
+    }
+
+    /// Sets whether the draft-04 forms of the `exclusiveMaximum` and
+    /// `exclusiveMinimum` keywords are accepted when creating schemas.
+    ///
+    /// In draft-04, these were booleans modifying `maximum` and `minimum`
+    /// rather than numbers. When this is on, `"exclusiveMaximum": true`
+    /// alongside `"maximum": 10` behaves like `"exclusiveMaximum": 10`. By
+    /// default, only the draft-06 (numeric) forms are accepted.
+    ///
+    /// The draft-06 metaschema rejects the boolean forms, so with the
+    /// `metaschema` feature, schemas using them have to be created with
+    /// `make_schema_unchecked`.
+    pub fn set_draft4_exclusive_bounds(&mut self, draft4: bool) {
+        self.draft4_exclusive_bounds = draft4;
+    }
+
+    /// Sets whether unknown keywords are rejected when creating schemas.
+    ///
+    /// The spec requires unknown keywords to be ignored, which is the default.
+    /// Turning this on makes them an error instead, which is useful for
+    /// catching typos while writing a schema.
+    ///
+    /// It also makes a `type` list that repeats a type an error, as the spec
+    /// requires; otherwise the repeats are dropped.
+    pub fn set_strict_keywords(&mut self, strict: bool) {
+        self.strict_keywords = strict;
+    }
+
+    /// Sets whether identical subschemas of a schema being created are
+    /// stored once, rather than once per place they're written. This saves memory
+    /// and parsing time for schemas that repeat e.g. `{"type": "string"}`
+    /// under many properties. It's off by default, and only affects schemas
+    /// created after it's set.
+    ///
+    /// Only subschemas without subschemas of their own, `$id`, or
+    /// `definitions` are shared, and only when their JSON is exactly the same.
+    /// A shared subschema can still be found by its own URI (e.g. with `get`,
+    /// or a `$ref` to its JSON pointer), but doesn't count towards `len`, and
+    /// isn't written out separately by `to_bundle`.
+    pub fn set_dedup_subschemas(&mut self, dedup: bool) {
+        self.dedup_subschemas = dedup;
+    }
+
+    /// Finds the schema among the given URIs that the value fits best, that is,
+    /// the one with the fewest validation errors, along with that number of
+    /// errors. Ties are broken in favor of the earlier URI, and URIs that
+    /// aren't in the Context are skipped.
+    ///
+    /// This validates the value against every candidate, so it takes time
+    /// linear in the number of candidate schemas.
+    pub fn best_match<'a>(&'a self, uris: &[Url], json: &Value) -> Option<(&'a Url, usize)> {
+        uris.iter()
+            .filter_map(|uri| self.find(uri))
+            .map(|(uri, inner)| {
+                let mut errors = 0;
+                inner.validator.validate_each(self, json, &mut |_| {
+                    errors += 1;
+                    true
+                });
+                (uri, errors)
             })
+            .min_by_key(|&(_, errors)| errors)
+    }
+
+    /// Returns the first of the given URIs whose schema the value is valid
+    /// against, or `None` if there isn't one. URIs that aren't in the Context
+    /// are skipped.
+    pub fn classify(&self, candidates: &[Url], json: &Value) -> Option<Url> {
+        candidates.iter()
+            .find(|uri| self.get(uri).map(|schema| schema.is_valid(json)).unwrap_or(false))
+            .cloned()
+    }
+
+    /// Returns whether the two Contexts hold the same schemas under the same
+    /// URIs, ignoring annotations like `title` and the order of conditions.
+    ///
+    /// Schemas are compared as `get` finds them, so a Context made with
+    /// `set_dedup_subschemas` is equivalent to one made from the same schemas
+    /// without it.
+    pub fn equivalent(&self, other: &Context) -> bool {
+        let uris = self.schemas.keys().chain(self.dedup.aliases.keys()).collect::<BTreeSet<_>>();
+        self.anchors == other.anchors &&
+            uris == other.schemas.keys().chain(other.dedup.aliases.keys()).collect() &&
+            uris.into_iter().all(|uri| match (self.find_here(uri), other.find_here(uri)) {
+                (Some((_, a)), Some((_, b))) => a.validator.equivalent(&b.validator),
+                _ => false,
+            })
+    }
+
+    /// Returns the anchor URI that refers to the schema stored under the
+    /// given URI, if there is one.
+    pub(crate) fn anchor_of(&self, uri: &Url) -> Option<&Url> {
+        self.anchor_names.get(uri)
+    }
+
+    /// Returns the URI of the schema the given anchor URI refers to, if it's
+    /// been found by the current call to `parse_document`.
+    pub(crate) fn pending_anchor_target(&self, anchor: &Url) -> Option<&Url> {
+        self.pending_anchors.get(anchor)
+    }
+
+    /// Records that the given anchor URI refers to the schema stored under
+    /// the given URI. It can't be looked up with `get` until the document
+    /// it's in has finished parsing.
+    pub(crate) fn put_anchor(&mut self, anchor: Url, uri: Url) {
+        self.pending_anchors.insert(anchor, uri);
+    }
+
+    /// Parses a schema with `parse`, then replaces the anchors the document
+    /// had before with the ones found in it, so a document that fails to
+    /// parse doesn't leave any behind.
+    fn parse_document(&mut self, id: Url, json: &Value, depth: usize) -> Result<Url, FromValueError> {
+        let result = self.parse(id, json, depth);
+        let anchors = mem::take(&mut self.pending_anchors);
+        if let Ok(ref uri) = result {
+            self.remove_anchors(|target| is_within(target, uri));
+            if !anchors.is_empty() {
+                self.add_anchors(anchors);
+            }
+        }
+        result
+    }
+
+    /// Adds anchors, given as a map from each anchor URI to the URI of the
+    /// schema it refers to, replacing any that are already there.
+    fn add_anchors(&mut self, anchors: BTreeMap<Url, Url>) {
+        let names = Arc::make_mut(&mut self.anchor_names);
+        for (anchor, target) in &anchors {
+            if let Some(old) = self.anchors.get(anchor) {
+                names.remove(old);
+            }
+            names.insert(target.clone(), anchor.clone());
         }
+        Arc::make_mut(&mut self.anchors).extend(anchors);
+    }
+
+    /// Removes the anchors that refer to the schemas whose URIs `remove`
+    /// returns true for.
+    fn remove_anchors<F: Fn(&Url) -> bool>(&mut self, remove: F) {
+        retain(Arc::make_mut(&mut self.anchors), |_, target| !remove(target));
+        retain(Arc::make_mut(&mut self.anchor_names), |target, _| !remove(target));
     }
 
     /// Stores a JsonSchema into the context.
     pub(crate) fn put(&mut self, uri: Url, schema: JsonSchemaInner) {
-        self.schemas.insert(uri, schema);
+        if self.dedup.aliases.contains_key(&uri) {
+            Arc::make_mut(&mut self.dedup).aliases.remove(&uri);
+        }
+        Arc::make_mut(&mut self.schemas).insert(uri, schema);
+    }
+
+    /// Returns the key a subschema with the given URI can be deduplicated by,
+    /// if deduplication is on and it might be reusable. See
+    /// `set_dedup_subschemas`.
+    pub(crate) fn dedup_key(&self, uri: &Url, json: &Value, depth: usize) -> Option<(Url, String)> {
+        if !self.dedup_subschemas || depth == 0 {
+            return None;
+        }
+        if let Value::Object(ref obj) = *json {
+            if obj.contains_key("$id") || obj.contains_key("definitions") {
+                return None;
+            }
+        }
+        let mut document = uri.clone();
+        document.set_fragment(None);
+        Some((document, json.to_string()))
+    }
+
+    /// If a schema with the given key was stored earlier in the same call to
+    /// `parse`, records the URI as an alias for it and returns true.
+    pub(crate) fn dedup_reuse(&mut self, uri: &Url, key: &(Url, String)) -> bool {
+        let target = match self.dedup.index.get(key) {
+            Some(target) => target.clone(),
+            None => return false,
+        };
+        // Whatever was stored under the URI before would shadow the alias.
+        if self.schemas.contains_key(uri) {
+            Arc::make_mut(&mut self.schemas).remove(uri);
+        }
+        Arc::make_mut(&mut self.dedup).aliases.insert(uri.clone(), target);
+        true
+    }
+
+    /// Records that the schema stored under the URI can be reused for others
+    /// with the same key, until the end of the current call to `parse`.
+    pub(crate) fn dedup_record(&mut self, uri: Url, key: (Url, String)) {
+        Arc::make_mut(&mut self.dedup).index.insert(key, uri);
+    }
+
+    /// Forgets the schemas recorded by `dedup_record`.
+    pub(crate) fn dedup_clear(&mut self) {
+        if !self.dedup.index.is_empty() {
+            Arc::make_mut(&mut self.dedup).index.clear();
+        }
+    }
+}
+
+/// Checks that a JSON value is a well-formed schema, without adding it to a
+/// Context. This runs the same checks as `Context::make_schema_unchecked`
+/// does, so it catches keywords with the wrong type (e.g. a string
+/// `maximum`) and some invalid values (e.g. a `multipleOf` of zero), but
+/// doesn't validate against the metaschema.
+///
+/// `$ref`s aren't resolved, so a reference to a missing schema isn't an
+/// error. A relative `$id` or `$ref` is resolved against a placeholder base
+/// URI, so the URIs in the error may not match the schema's real location.
+pub fn is_well_formed(json: &Value) -> Result<(), FromValueError> {
+    let base_uri = Url::parse("json-schema:///schema.json")
+        .expect("Couldn't parse base URI");
+    let mut ctx = Context::empty();
+    ctx.parse_document(base_uri, json, 0).map(|_| ())
+}
+
+/// Returns a schema that only checks that values have the given type, as with
+/// `{"type": "string"}`. Its URI is `urn:json-schema:type:<type>`.
+///
+/// The schemas for every type live in a Context of their own, which is made
+/// the first time this is called, so they can't `$ref` or be `$ref`ed by
+/// schemas in other Contexts.
+pub fn type_schema(ty: Type) -> JsonSchema<'static> {
+    TYPE_SCHEMAS.get(&type_schema_uri(ty))
+        .expect("Type schema wasn't added")
+}
+
+/// Returns the URI of the schema `type_schema` returns for the type.
+fn type_schema_uri(ty: Type) -> Url {
+    Url::parse(&format!("urn:json-schema:type:{}", ty))
+        .expect("Couldn't parse type schema URI")
+}
+
+lazy_static! {
+    /// A Context holding a schema for each type, for `type_schema`.
+    static ref TYPE_SCHEMAS: Context = {
+        let mut ctx = Context::empty();
+        for &ty in Type::ALL.iter() {
+            ctx.put(type_schema_uri(ty), JsonSchemaInner {
+                annotations: Map::new(),
+                description: None,
+                diagnostics: Vec::new(),
+                format: None,
+                title: None,
+                validator: Validator::Conditions(vec![Condition::Type(vec![ty])]),
+            });
+        }
+        ctx
+    };
+}
+
+#[cfg(feature = "metaschema")]
+lazy_static! {
+    /// A Context holding just the metaschema, for `validate_schema`.
+    static ref METASCHEMA_CONTEXT: Context = Context::new();
+}
+
+/// Validates a JSON value against the draft-06 metaschema, to check that it's
+/// a valid schema without creating one from it.
+///
+/// This is the check `Context::make_schema` does first. Unlike
+/// `is_well_formed`, it catches everything the metaschema forbids (e.g. a
+/// `required` with duplicate names), but doesn't catch what the metaschema
+/// can't express, like a `pattern` that isn't a valid regex.
+#[cfg(feature = "metaschema")]
+pub fn validate_schema(json: &Value) -> Result<(), ValidationError> {
+    METASCHEMA_CONTEXT.validate(&METASCHEMA_URI, json)
+}
+
+/// Takes the value out of an `Arc`, cloning it if the `Arc` is shared.
+fn unwrap_or_clone<T: Clone>(arc: Arc<T>) -> T {
+    Arc::try_unwrap(arc).unwrap_or_else(|arc| (*arc).clone())
+}
+
+/// Removes the entries of the map that `keep` returns false for, like
+/// `BTreeMap::retain`, which needs a newer Rust than this crate supports.
+fn retain<K: Clone + Ord, V, F: FnMut(&K, &V) -> bool>(map: &mut BTreeMap<K, V>, mut keep: F) {
+    let removed = map.iter()
+        .filter(|&(k, v)| !keep(k, v))
+        .map(|(k, _)| k.clone())
+        .collect::<Vec<_>>();
+    for k in removed {
+        map.remove(&k);
+    }
+}
+
+/// Returns whether the URI is the same as `prefix`, or points inside of the
+/// schema `prefix` points to.
+fn is_within(uri: &Url, prefix: &Url) -> bool {
+    if !same_document(uri, prefix) {
+        return false;
+    }
+    let prefix = prefix.fragment().unwrap_or("");
+    let fragment = uri.fragment().unwrap_or("");
+    prefix.is_empty() || fragment == prefix ||
+        (fragment.starts_with(prefix) && fragment[prefix.len()..].starts_with('/'))
+}
+
+impl Default for Context {
+    fn default() -> Context {
+        Context::new()
     }
 }
@@ -0,0 +1,200 @@
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+
+/// A wrapper around a JSON value whose `Eq` and `Hash` impls follow
+/// `json_eq`, so that values can be put in a `HashSet` or `HashMap` and
+/// compared as the spec requires.
+#[derive(Clone, Copy, Debug)]
+pub struct CanonicalValue<'a>(pub &'a Value);
+
+impl<'a> PartialEq for CanonicalValue<'a> {
+    fn eq(&self, other: &CanonicalValue<'a>) -> bool {
+        json_eq(self.0, other.0)
+    }
+}
+
+impl<'a> Eq for CanonicalValue<'a> {}
+
+impl<'a> Hash for CanonicalValue<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        canonical_string(self.0).hash(state)
+    }
+}
+
+/// Compares two JSON values for equality as defined by the spec, which is
+/// used by the `const`, `enum`, and `uniqueItems` keywords.
+///
+/// This differs from `Value`'s `PartialEq` impl only for numbers, which are
+/// compared by their mathematical value rather than their representation. For
+/// example, `1` equals `1.0`, and `0` equals `-0.0`. (JSON has no `NaN`, so
+/// that case can't arise.)
+///
+/// Two values are equal exactly when their `canonical_string`s are equal.
+pub fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (&Value::Number(ref a), &Value::Number(ref b)) => number_eq(a, b),
+        (&Value::Array(ref a), &Value::Array(ref b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        },
+        (&Value::Object(ref a), &Value::Object(ref b)) => {
+            a.len() == b.len() && a.iter().all(|(k, a)| {
+                b.get(k).map(|b| json_eq(a, b)).unwrap_or(false)
+            })
+        },
+        _ => a == b,
+    }
+}
+
+/// Serializes a JSON value such that two values serialize to the same string
+/// exactly when they are equal according to `json_eq`. Object keys are
+/// sorted, and integral numbers are always written without a fractional
+/// part.
+pub fn canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(&mut out, value);
+    out
+}
+
+fn write_canonical(out: &mut String, value: &Value) {
+    match *value {
+        Value::Array(ref arr) => {
+            out.push('[');
+            for (i, v) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, v);
+            }
+            out.push(']');
+        },
+        Value::Number(ref n) => write_number(out, n),
+        Value::Object(ref obj) => {
+            let mut keys = obj.keys().collect::<Vec<_>>();
+            keys.sort();
+            out.push('{');
+            for (i, k) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, &Value::String(k.to_owned()));
+                out.push(':');
+                write_canonical(out, &obj[k]);
+            }
+            out.push('}');
+        },
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+/// Serializes a number as `canonical_string` would.
+pub fn canonical_number(n: &Number) -> String {
+    let mut out = String::new();
+    write_number(&mut out, n);
+    out
+}
+
+fn write_number(out: &mut String, n: &Number) {
+    if let Some(n) = n.as_u64() {
+        write!(out, "{}", n)
+    } else if let Some(n) = n.as_i64() {
+        write!(out, "{}", n)
+    } else {
+        let f = n.as_f64().expect("Number wasn't u64, i64, or f64");
+        if let Some(n) = f64_as_u64(f) {
+            write!(out, "{}", n)
+        } else if let Some(n) = f64_as_i64(f) {
+            write!(out, "{}", n)
+        } else {
+            write!(out, "{:?}", f)
+        }
+    }.expect("Writing to a String failed")
+}
+
+/// Compares two numbers by their mathematical value. Integers are compared
+/// exactly, even against floats.
+fn number_eq(a: &Number, b: &Number) -> bool {
+    if let Some(a) = a.as_u64() {
+        b.as_u64().or_else(|| b.as_f64().and_then(f64_as_u64)) == Some(a)
+    } else if let Some(a) = a.as_i64() {
+        b.as_i64().or_else(|| b.as_f64().and_then(f64_as_i64)) == Some(a)
+    } else if b.is_u64() || b.is_i64() {
+        number_eq(b, a)
+    } else {
+        a.as_f64() == b.as_f64()
+    }
+}
+
+/// Compares two numbers by their mathematical value. Like `number_eq`,
+/// integers are compared exactly, even against floats, where converting the
+/// integer to a float could round it.
+pub fn number_cmp(a: &Number, b: &Number) -> Option<Ordering> {
+    if a.is_f64() && b.is_f64() {
+        return a.as_f64().partial_cmp(&b.as_f64());
+    } else if a.is_f64() {
+        return number_cmp(b, a).map(Ordering::reverse);
+    }
+
+    // `a` is an integer.
+    if let (Some(a), Some(b)) = (a.as_u64(), number_as_u64(b)) {
+        Some(a.cmp(&b))
+    } else if let (Some(a), Some(b)) = (a.as_i64(), number_as_i64(b)) {
+        Some(a.cmp(&b))
+    } else if b.is_u64() {
+        // `a` is negative.
+        Some(Ordering::Less)
+    } else if b.is_i64() {
+        // `a` is too large to be an `i64`, and `b` is negative.
+        Some(Ordering::Greater)
+    } else {
+        let f = b.as_f64().expect("Number wasn't u64, i64, or f64");
+        if f >= 18446744073709551616.0 {
+            Some(Ordering::Less)
+        } else if f < -9223372036854775808.0 {
+            Some(Ordering::Greater)
+        } else {
+            // Either `f` isn't integral, so it's small enough that integers
+            // near it convert exactly, or it's integral and the other sign.
+            let a = a.as_f64().expect("Number wasn't u64, i64, or f64");
+            a.partial_cmp(&f)
+        }
+    }
+}
+
+/// Converts a number to a `u64`, if it's integral and in range, even if it's
+/// written as a float (e.g. `1e3`).
+pub fn number_as_u64(n: &Number) -> Option<u64> {
+    n.as_u64().or_else(|| n.as_f64().and_then(f64_as_u64))
+}
+
+/// Converts a number to an `i64`, if it's integral and in range, even if it's
+/// written as a float (e.g. `-1e3`).
+pub fn number_as_i64(n: &Number) -> Option<i64> {
+    n.as_i64().or_else(|| n.as_f64().and_then(f64_as_i64))
+}
+
+/// Returns whether a number is integral, however it's written. Integers too
+/// large for a `u64` or `i64` are stored as `f64`s, and so are counted as long
+/// as they have no fractional part.
+pub fn number_is_integral(n: &Number) -> bool {
+    n.is_u64() || n.is_i64() || n.as_f64().map(|f| f.fract() == 0.0).unwrap_or(false)
+}
+
+/// Converts an `f64` to a `u64`, if it's integral and in range.
+fn f64_as_u64(f: f64) -> Option<u64> {
+    if f.fract() == 0.0 && (0.0..18446744073709551616.0).contains(&f) {
+        Some(f as u64)
+    } else {
+        None
+    }
+}
+
+/// Converts an `f64` to an `i64`, if it's integral and in range.
+fn f64_as_i64(f: f64) -> Option<i64> {
+    if f.fract() == 0.0 && (-9223372036854775808.0..9223372036854775808.0).contains(&f) {
+        Some(f as i64)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,35 @@
+use std::ops::Deref;
+use super::Context;
+
+/// A [`Context`](struct.Context.html) whose references are all known to
+/// resolve, as returned by
+/// [`Context::freeze`](struct.Context.html#method.freeze).
+///
+/// It dereferences to the Context, but doesn't allow changing it, so a
+/// `BadReference` error can only come from asking for a schema that isn't in
+/// it, never from a `$ref` inside one. Use `thaw` to get the Context back
+/// for adding more schemas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrozenContext(Context);
+
+impl FrozenContext {
+    /// Wraps a Context, which must have no unresolved references.
+    pub(crate) fn new(ctx: Context) -> FrozenContext {
+        FrozenContext(ctx)
+    }
+
+    /// Returns the Context this wraps.
+    pub fn as_context(&self) -> &Context {
+        &self.0
+    }
+
+    /// Unwraps the Context, so schemas can be added to it again.
+    pub fn thaw(self) -> Context {
+        self.0
+    }
+}
+
+impl Deref for FrozenContext {
+    type Target = Context;
+    fn deref(&self) -> &Context { &self.0 }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use url::{ParseError, Url};
+
+lazy_static! {
+    /// The number to use in the next anonymous ID.
+    static ref NEXT_ANONYMOUS: AtomicUsize = AtomicUsize::new(0);
+}
+
+/// The URI identifying a schema.
+///
+/// This wraps a [`Url`](https://docs.rs/url/1/url/struct.Url.html), so that
+/// schemas can be named without depending on the `url` crate directly. It
+/// dereferences to the `Url`, and converts to and from one, for when more
+/// control is needed.
+///
+/// ```
+/// # extern crate json_schema;
+/// # #[macro_use] extern crate serde_json;
+/// # use json_schema::{Context, SchemaId};
+/// # fn main() {
+/// let id = "http://example.com/schema.json".parse::<SchemaId>().unwrap();
+/// let mut ctx = Context::default();
+/// ctx.make_schema(id.clone(), &json!({"type": "integer"})).unwrap();
+/// assert!(ctx.get(&id).is_some());
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SchemaId(Url);
+
+impl SchemaId {
+    /// Returns a new ID of the form `urn:json-schema:anonymous:<n>`, which is
+    /// different from every other one returned by this function. This is
+    /// useful for schemas that don't have a natural URI.
+    ///
+    /// Relative `$ref`s can't be resolved against such an ID, so schemas
+    /// given one should only use absolute URIs or fragments.
+    pub fn anonymous() -> SchemaId {
+        let n = NEXT_ANONYMOUS.fetch_add(1, Ordering::Relaxed);
+        let uri = Url::parse(&format!("urn:json-schema:anonymous:{}", n))
+            .expect("Couldn't parse anonymous URI");
+        SchemaId(uri)
+    }
+
+    /// Returns the ID for a file, as a `file://` URI. The path must be
+    /// absolute.
+    pub fn from_file_path<P: AsRef<Path>>(path: P) -> Option<SchemaId> {
+        Url::from_file_path(path).ok().map(SchemaId)
+    }
+
+    /// Returns the `Url` this ID wraps.
+    pub fn as_url(&self) -> &Url {
+        &self.0
+    }
+
+    /// Unwraps the `Url` this ID wraps.
+    pub fn into_url(self) -> Url {
+        self.0
+    }
+}
+
+impl Deref for SchemaId {
+    type Target = Url;
+    fn deref(&self) -> &Url { &self.0 }
+}
+
+impl Display for SchemaId {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        Display::fmt(&self.0, fmt)
+    }
+}
+
+impl FromStr for SchemaId {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<SchemaId, ParseError> {
+        Url::parse(s).map(SchemaId)
+    }
+}
+
+impl From<Url> for SchemaId {
+    fn from(uri: Url) -> SchemaId {
+        SchemaId(uri)
+    }
+}
+
+impl<'a> From<&'a Url> for SchemaId {
+    fn from(uri: &'a Url) -> SchemaId {
+        SchemaId(uri.clone())
+    }
+}
+
+impl From<SchemaId> for Url {
+    fn from(id: SchemaId) -> Url {
+        id.0
+    }
+}
@@ -1,14 +1,31 @@
 mod condition;
 mod context;
+mod equality;
+mod frozen;
+mod id;
 mod parse;
+mod size;
 mod validator;
 
-use errors::ValidationError;
-use serde_json::Value;
+use annotations::Annotations;
+use errors::{Error, ValidationError};
+use lint::{Diagnostic, Lint};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::sync::Arc;
+use stream::ValidateReader;
+use tree::ErrorTree;
 use url::Url;
 
-pub use self::condition::{Condition, RegexWrapper, Type};
-pub use self::context::Context;
+pub use leaf::Type;
+pub use self::condition::{Condition, Discriminator, RegexWrapper};
+pub use self::context::{is_well_formed, type_schema, Context};
+#[cfg(feature = "metaschema")]
+pub use self::context::validate_schema;
+pub use self::frozen::FrozenContext;
+pub use self::id::SchemaId;
 pub use self::validator::Validator;
 
 /// A JSON Schema. See the crate's documentation for more information and usage
@@ -24,30 +41,409 @@ impl<'a> JsonSchema<'a> {
     /// Creates a JSON value from a JSON Schema. This can be used to serialize
     /// the JsonSchema in lieu of a Serialize impl.
     pub fn to_value(&self) -> Value {
-        self.inner.to_value()
+        self.inner.to_value(self.ctx, &self.id)
+    }
+
+    /// Creates a JSON value from a JSON Schema, keeping as much of the
+    /// original document as was retained. Unlike `to_value`, this includes
+    /// the schema's `$id` (as an absolute URI, using its anchor if it has
+    /// one), and its `$schema` if it had one.
+    ///
+    /// Some keywords are lost when a schema is parsed, and so are missing
+    /// here too:
+    ///
+    ///  - Unknown keywords.
+    ///  - `definitions`, although the schemas in it are still in the Context
+    ///    under their own URIs (see
+    ///    [`Context::to_bundle`](struct.Context.html#method.to_bundle)).
+    ///  - Keywords alongside a `$ref`, other than `title`, `description`,
+    ///    `format`, `default`, and `examples`.
+    ///  - The draft-07 annotations, like `readOnly` and `$comment`.
+    ///
+    /// Conditions are also written in their parsed form, so for example a
+    /// `type` with a single element comes back as a string.
+    pub fn to_value_full(&self) -> Value {
+        let mut value = self.to_document_value();
+        if let Value::Object(ref mut obj) = value {
+            let id = self.ctx.anchor_of(&self.id).unwrap_or(&self.id);
+            obj.insert("$id".to_string(), Value::String(id.to_string()));
+        }
+        value
+    }
+
+    /// Creates a JSON value from a JSON Schema, as the root of its own
+    /// document. This is `to_value_in` its own URI, plus the `$schema`.
+    pub(crate) fn to_document_value(&self) -> Value {
+        let mut value = self.to_value_in(&self.id);
+        if let Value::Object(ref mut obj) = value {
+            if let Some(schema) = self.inner.annotations.get("$schema") {
+                obj.insert("$schema".to_string(), schema.clone());
+            }
+        }
+        value
+    }
+
+    /// Creates a JSON value from a JSON Schema, as written inside the schema
+    /// with the URI `parent`. If this schema's URI isn't the one it'd get
+    /// from its position in the parent, an `$id` is added to keep it.
+    pub(crate) fn to_value_in(&self, parent: &Url) -> Value {
+        let mut value = self.to_value();
+        if let Value::Object(ref mut obj) = value {
+            let is_pointer = self.id.fragment().map(|f| f.starts_with('/')).unwrap_or(false);
+            let id = if let Some(anchor) = self.ctx.anchor_of(&self.id) {
+                anchor.fragment().map(|f| format!("#{}", f))
+            } else if self.id == *parent || (is_pointer && same_document(&self.id, parent)) {
+                None
+            } else {
+                Some(self.id.to_string())
+            };
+            if let Some(id) = id {
+                obj.insert("$id".to_string(), Value::String(id));
+            }
+        }
+        value
+    }
+
+    /// Returns the URI the schema is stored under in its Context. For a
+    /// subschema, this is the URI it was given by `$id`, or its JSON pointer
+    /// within the document otherwise.
+    pub fn id(&self) -> &Url {
+        &self.id
+    }
+
+    /// Returns the schema's `title`, if it has one.
+    ///
+    /// A schema that's just a `$ref` has the title written next to the
+    /// `$ref`, not the one of the schema it refers to; use
+    /// [`Context::get`](struct.Context.html#method.get) with the reference
+    /// to get that.
+    pub fn title(&self) -> Option<&'a str> {
+        self.inner.title.as_deref()
+    }
+
+    /// Returns the schema's `description`, if it has one. Like `title`, this
+    /// isn't looked up through a `$ref`.
+    pub fn description(&self) -> Option<&'a str> {
+        self.inner.description.as_deref()
+    }
+
+    /// Returns the keywords in the schema that were accepted but aren't
+    /// enforced when validating, such as `format`, for auditing what a schema
+    /// actually checks.
+    ///
+    /// Only this schema's own keywords are included; each subschema has its
+    /// own diagnostics, which can be found with
+    /// [`Context::get`](struct.Context.html#method.get).
+    pub fn diagnostics(&self) -> &'a [Diagnostic] {
+        &self.inner.diagnostics
+    }
+
+    /// Returns the value of the schema's `default` keyword, if it has one.
+    /// This doesn't affect validation, and isn't checked against the schema
+    /// (but see `lint`).
+    pub fn default_value(&self) -> Option<&'a Value> {
+        self.inner.annotations.get("default")
+    }
+
+    /// Returns the values given in the schema's `examples` keyword, or an
+    /// empty slice if there isn't one. These don't affect validation.
+    pub fn examples(&self) -> &'a [Value] {
+        match self.inner.annotations.get("examples") {
+            Some(&Value::Array(ref examples)) => examples,
+            _ => &[],
+        }
+    }
+
+    /// Returns the values this schema allows, if it only allows a fixed set
+    /// of them (by `enum` or `const`), or `None` otherwise. This is useful
+    /// for generating things like dropdowns.
+    ///
+    /// A `$ref`, or the subschemas of an `allOf`, are looked through, but only
+    /// one level deep. Values that fail some other part of the schema are
+    /// left out.
+    pub fn allowed_values(&self) -> Option<Vec<Value>> {
+        self.value_set(true).map(|values| {
+            values.into_iter()
+                .filter(|v| self.validate(v).is_ok())
+                .collect()
+        })
+    }
+
+    /// Returns the values from the first `enum` or `const` in this schema,
+    /// looking through a `$ref` or `allOf` if `follow` is true.
+    fn value_set(&self, follow: bool) -> Option<Vec<Value>> {
+        match self.inner.validator {
+            Validator::Anything => None,
+            Validator::Conditions(ref c) => c.iter().filter_map(|c| match *c {
+                Condition::Const(ref v) => Some(vec![v.clone()]),
+                Condition::Enum(ref vs) => Some(vs.clone()),
+                Condition::AllOf(ref urls, _) if follow => urls.iter()
+                    .filter_map(|uri| self.ctx.get(uri))
+                    .filter_map(|schema| schema.value_set(false))
+                    .next(),
+                _ => None,
+            }).next(),
+            Validator::Nothing => Some(Vec::new()),
+            Validator::Reference(ref r) if follow => self.ctx.get(r)
+                .and_then(|schema| schema.value_set(false)),
+            Validator::Reference(_) => None,
+        }
+    }
+
+    /// Returns the names of the keywords this schema checks, in the order
+    /// they're checked in. A schema that's just a `$ref` returns `["$ref"]`.
+    ///
+    /// This is mainly useful for debugging why a particular error is
+    /// reported when a value fails several conditions.
+    pub fn evaluation_order(&self) -> Vec<&'static str> {
+        match self.inner.validator {
+            Validator::Conditions(ref c) => c.iter().map(|c| c.keyword()).collect(),
+            Validator::Reference(_) => vec!["$ref"],
+            Validator::Anything | Validator::Nothing => Vec::new(),
+        }
+    }
+
+    /// Validates a JSON value using only the condition created from the given
+    /// keyword, or returns `None` if the schema doesn't use it. Keywords are
+    /// named as in `evaluation_order`, so for example `additionalItems` is
+    /// checked as part of `items`.
+    ///
+    /// For a schema that's just a `$ref`, the only keyword is `$ref`, which
+    /// validates against the referenced schema in full.
+    pub fn validate_keyword(&self, keyword: &str, json: &Value) -> Option<Result<(), ValidationError>> {
+        match self.inner.validator {
+            Validator::Conditions(ref c) => c.iter()
+                .find(|c| c.keyword() == keyword)
+                .map(|c| c.validate(self.ctx, json)),
+            Validator::Reference(_) if keyword == "$ref" => Some(self.validate(json)),
+            Validator::Reference(_) | Validator::Anything | Validator::Nothing => None,
+        }
     }
 
     /// Validates a JSON value using this schema.
     pub fn validate(&self, json: &Value) -> Result<(), ValidationError> {
         self.inner.validator.validate(self.ctx, json)
     }
+
+    /// Validates a JSON value using this schema, returning the value after any
+    /// coercions have been applied. Coercion is only done in lenient mode (see
+    /// [`Context::set_coerce_types`](struct.Context.html#method.set_coerce_types));
+    /// otherwise, this returns the value unchanged if it's valid.
+    pub fn validate_coerce(&self, json: &Value) -> Result<Value, ValidationError> {
+        let mut json = json.clone();
+        if self.ctx.coerce_types {
+            self.inner.validator.coerce(self.ctx, &mut json);
+        }
+        self.validate(&json)?;
+        Ok(json)
+    }
+
+    /// Validates a JSON value using this schema, then deserializes it into a
+    /// `T`. The value is only deserialized if it's valid, so `T`'s
+    /// `Deserialize` impl can rely on the schema's guarantees.
+    ///
+    /// A value that's valid but doesn't deserialize results in an
+    /// `Error::Deserialize`. Types aren't coerced, even in lenient mode; use
+    /// `validate_coerce` and `serde_json::from_value` for that.
+    pub fn validate_into<T: DeserializeOwned>(&self, json: &Value) -> Result<T, Error> {
+        self.validate(json)?;
+        ::serde_json::from_value(json.clone())
+            .map_err(|err| Error::Deserialize(Arc::new(err)))
+    }
+
+    /// Validates a JSON value using this schema, checking every condition and
+    /// arranging the errors by where in the value they occurred. See
+    /// [`ErrorTree`](struct.ErrorTree.html) for details.
+    ///
+    /// Unlike `validate_all`, this descends into `items`, `properties`, and
+    /// `allOf`, so a subschema can report more than one error.
+    pub fn validate_tree(&self, json: &Value) -> ErrorTree {
+        let mut tree = ErrorTree::default();
+        self.inner.validator.validate_tree(self.ctx, json, &mut tree);
+        tree
+    }
+
+    /// Validates a JSON value using this schema, returning the annotations
+    /// collected from the parts of the schema that applied to it. See
+    /// [`Annotations`](struct.Annotations.html) for details.
+    ///
+    /// This is slower than `validate` on success, since every branch of an
+    /// `anyOf` is checked rather than just the first that matches.
+    pub fn validate_annotated(&self, json: &Value) -> Result<Annotations, ValidationError> {
+        self.validate(json)?;
+        let mut annotations = Annotations::default();
+        self.annotate(json, &mut Vec::new(), &mut annotations);
+        Ok(annotations)
+    }
+
+    /// Adds the annotations from a value, which must be valid according to
+    /// this schema, at the given path.
+    pub(crate) fn annotate(&self, json: &Value, path: &mut Vec<String>, out: &mut Annotations) {
+        if let Some(ref format) = self.inner.format {
+            out.push_format(path, format);
+        }
+        self.inner.validator.annotate(self.ctx, json, path, out);
+    }
+
+    /// Returns whether a JSON value is valid according to this schema. This is
+    /// faster than `validate(json).is_ok()`, since no errors are constructed,
+    /// but always agrees with it: a reference that `validate` would report as
+    /// unresolved (or as a `ReferenceCycle`) makes this return false, even
+    /// under `not`.
+    pub fn is_valid(&self, json: &Value) -> bool {
+        self.check(json).unwrap_or(false)
+    }
+
+    /// Returns whether a JSON value is valid according to this schema, or
+    /// `None` if `validate` would return a resolution error, which keywords
+    /// like `not` mustn't invert.
+    pub(crate) fn check(&self, json: &Value) -> Option<bool> {
+        self.inner.validator.check(self.ctx, json)
+    }
+
+    /// Validates newline-delimited JSON read from the given reader, yielding
+    /// one result per line without loading the whole input into memory. See
+    /// [`ValidateReader`](struct.ValidateReader.html) for details.
+    pub fn validate_reader<R: BufRead>(&self, reader: R) -> ValidateReader<'a, R> {
+        ValidateReader::new(self.ctx, self.id.clone(), reader)
+    }
+
+    /// Validates a JSON value using this schema, checking every condition
+    /// rather than stopping at the first failure.
+    ///
+    /// Subschemas (for example, those in `allOf` or `properties`) still stop
+    /// at their first failure, so each failing condition of this schema
+    /// contributes exactly one error.
+    pub fn validate_all(&self, json: &Value) -> Result<(), Vec<ValidationError>> {
+        let errors = self.inner.validator.validate_all(self.ctx, json);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validates a JSON value using this schema, passing the errors to `sink`
+    /// one at a time instead of collecting them, for callers that only count
+    /// or log them.
+    ///
+    /// Unlike `validate_all`, this also checks every condition of the
+    /// subschemas that `allOf`, `items`, and `properties` (along with
+    /// `patternProperties` and the `additional` keywords) apply, so a
+    /// subschema can contribute several errors. Each is wrapped the way
+    /// `validate` would wrap it, e.g. in an `AllOfBranchFailed`. Other
+    /// conditions contribute one error each, as with `validate_all`.
+    ///
+    /// The sink returns whether to keep going; returning false skips the
+    /// remaining conditions. Returns true if every condition was checked,
+    /// i.e. the sink never asked to stop.
+    pub fn validate_each<F: FnMut(ValidationError) -> bool>(&self, json: &Value, mut sink: F) -> bool {
+        self.inner.validator.validate_each(self.ctx, json, &mut sink)
+    }
+
+    /// Checks the schema for likely mistakes, namely a `default` or element
+    /// of `examples` that the schema it's in would reject. These don't affect
+    /// creating or using the schema.
+    ///
+    /// The subschemas in the same document are checked too, including ones
+    /// reached by `$ref`. `definitions` that are never referenced aren't.
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        self.lint_into(&mut BTreeSet::new(), &mut lints);
+        lints
+    }
+
+    fn lint_into(&self, seen: &mut BTreeSet<Url>, lints: &mut Vec<Lint>) {
+        if !seen.insert(self.id.clone()) {
+            return;
+        }
+        if let Some(default) = self.default_value() {
+            if let Err(err) = self.validate(default) {
+                lints.push(Lint::DefaultViolatesSchema(self.id.clone(), err));
+            }
+        }
+        for (i, example) in self.examples().iter().enumerate() {
+            if let Err(err) = self.validate(example) {
+                lints.push(Lint::ExampleViolatesSchema(self.id.clone(), i, err));
+            }
+        }
+        for uri in self.inner.validator.subschemas() {
+            if same_document(uri, &self.id) {
+                if let Some(schema) = self.ctx.get(uri) {
+                    schema.lint_into(seen, lints);
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether two schemas check the same constraints. Keyword order and
+/// annotations (like `title`, `description`, and `default`) don't matter, but
+/// this is a structural comparison rather than a logical one: for example,
+/// `anyOf` branches in a different order, or `false` and `{"not": {}}`, are
+/// considered different.
+///
+/// If either schema doesn't parse, returns `false`. The schemas aren't
+/// validated against the metaschema, even with the `metaschema` feature.
+pub fn schemas_equivalent(a: &Value, b: &Value) -> bool {
+    let base_uri = Url::parse("urn:json-schema:equivalent")
+        .expect("Couldn't parse base URI");
+    let mut ctx_a = Context::empty();
+    let mut ctx_b = Context::empty();
+    ctx_a.make_schema_unchecked(base_uri.clone(), a).is_ok() &&
+        ctx_b.make_schema_unchecked(base_uri, b).is_ok() &&
+        ctx_a.equivalent(&ctx_b)
+}
+
+/// Returns whether two URIs are the same, ignoring their fragments.
+fn same_document(a: &Url, b: &Url) -> bool {
+    a[..::url::Position::AfterQuery] == b[..::url::Position::AfterQuery]
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct JsonSchemaInner {
+    /// The `$schema`, `default`, and `examples` keywords, as they were given.
+    annotations: Map<String, Value>,
     description: Option<String>,
+    /// The keywords that were accepted but aren't enforced.
+    diagnostics: Vec<Diagnostic>,
+    /// The `format` keyword. This isn't checked, only reported by
+    /// `validate_annotated`.
+    format: Option<String>,
     title: Option<String>,
     validator: Validator,
 }
 
 impl JsonSchemaInner {
-    fn to_value(&self) -> Value {
-        let map = self.validator.to_json_object();
-        // TODO Add the other JSON Schema properties.
+    fn to_value(&self, ctx: &Context, id: &Url) -> Value {
+        if self.title.is_none() && self.description.is_none() && self.format.is_none() && self.annotations.is_empty() {
+            match self.validator {
+                Validator::Anything => return Value::Bool(true),
+                Validator::Nothing => return Value::Bool(false),
+                _ => {},
+            }
+        }
+
+        let mut map = self.validator.to_json_object(ctx, id);
+        if let Some(ref title) = self.title {
+            map.insert("title".to_string(), Value::String(title.to_string()));
+        }
+        if let Some(ref description) = self.description {
+            map.insert("description".to_string(), Value::String(description.to_string()));
+        }
+        if let Some(ref format) = self.format {
+            map.insert("format".to_string(), Value::String(format.to_string()));
+        }
+        for (k, v) in &self.annotations {
+            if k != "$schema" {
+                map.insert(k.clone(), v.clone());
+            }
+        }
         Value::Object(map)
     }
 }
 
+#[cfg(feature = "metaschema")]
 lazy_static! {
     /// The URI corresponding to the draft-06 metaschema.
     pub static ref METASCHEMA_URI: Url = {
@@ -1,14 +1,33 @@
+use either::Either;
 use errors::FromValueError;
-use json_pointer::JsonPointer;
-use serde_json::Value;
-use super::{Condition, Context, JsonSchemaInner, RegexWrapper, Type, Validator};
+use lint::Diagnostic;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use super::{Condition, Context, Discriminator, JsonSchemaInner, RegexWrapper, Type, Validator};
+use super::condition::nothing_branch;
+use super::equality::number_as_u64;
 use url::Url;
+use url::percent_encoding::{utf8_percent_encode, DEFAULT_ENCODE_SET};
 
 impl Context {
     pub(crate) fn parse(&mut self, id: Url, json: &Value, depth: usize) -> Result<Url, FromValueError> {
-        let (validator, id, title, description) = match *json {
-            Value::Bool(true) => (Validator::Anything, id, None, None),
-            Value::Bool(false) => (Validator::Nothing, id, None, None),
+        // The schemas that can be reused are only remembered while parsing a
+        // single document. A failed parse may leave some behind, so they're
+        // cleared at the start as well as the end.
+        if depth == 0 {
+            self.dedup_clear();
+        }
+        let dedup_key = self.dedup_key(&id, json, depth);
+        if let Some(ref key) = dedup_key {
+            if self.dedup_reuse(&id, key) {
+                return Ok(id);
+            }
+        }
+
+        let mut diagnostics = Vec::new();
+        let (validator, id, title, description, format, annotations) = match *json {
+            Value::Bool(true) => (Validator::Anything, id, None, None, None, Map::new()),
+            Value::Bool(false) => (Validator::Nothing, id, None, None, None, Map::new()),
             Value::Object(ref obj) => {
                 // Validate the `$schema` field.
                 if let Some(val) = obj.get("$schema") {
@@ -16,7 +35,8 @@ impl Context {
                         return Err(FromValueError::SubschemaUsesSchemaKeyword(json.clone()));
                     }
                     if let Value::String(ref schema) = *val {
-                        if schema != "http://json-schema.org/draft-06/schema#" {
+                        let draft7 = cfg!(feature = "draft7") && schema == "http://json-schema.org/draft-07/schema#";
+                        if schema != "http://json-schema.org/draft-06/schema#" && !draft7 {
                             return Err(FromValueError::UnknownSchemaVersion(json.clone(), schema.to_owned()));
                         }
                     } else {
@@ -24,15 +44,30 @@ impl Context {
                     }
                 }
     
-                // Get `$id`. We're a little stricter than the RFC; a Schema with
-                // an `$id` whose fragment is non-empty will be rejected.
+                // Get `$id`. If it's just a plain-name fragment (e.g. `#foo`),
+                // it's an anchor for this schema, which is still stored under
+                // the URI it'd have otherwise.
                 let id = if let Some(val) = obj.get("$id") {
-                    if let Value::String(ref id) = *val {
-                        let id = Url::parse(id).map_err(|e| {
-                            FromValueError::InvalidId(json.clone(), id.to_owned(), e)
+                    if let Value::String(ref s) = *val {
+                        let new_id = id.join(s).map_err(|e| {
+                            FromValueError::InvalidId(json.clone(), s.to_owned(), e)
                         })?;
-                        // TODO Validate `$id`.
-                        id
+                        match new_id.fragment() {
+                            Some(f) if !f.is_empty() && !f.starts_with('/') => {
+                                // Two schemas with the same anchor would make
+                                // it resolve to whichever was parsed last. An
+                                // anchor from an earlier version of this
+                                // document is dropped once it has parsed.
+                                if let Some(existing) = self.pending_anchor_target(&new_id) {
+                                    if *existing != id {
+                                        return Err(FromValueError::URIConflict(json.clone(), new_id));
+                                    }
+                                }
+                                self.put_anchor(new_id.clone(), id.clone());
+                                id
+                            },
+                            _ => new_id,
+                        }
                     } else {
                         return Err(FromValueError::InvalidKeywordType(json.clone(), "$id".to_string(), val.clone()));
                     }
@@ -61,7 +96,42 @@ impl Context {
                 } else {
                     None
                 };
+
+                // Get the `format`, if it exists. It isn't checked, but it's
+                // reported by `validate_annotated`.
+                let format = if let Some(val) = obj.get("format") {
+                    if let Value::String(ref format) = *val {
+                        diagnostics.push(Diagnostic::FormatNotEnforced(format.to_owned()));
+                        Some(format.to_owned())
+                    } else {
+                        return Err(FromValueError::InvalidKeywordType(json.clone(), "format".to_string(), val.clone()));
+                    }
+                } else {
+                    None
+                };
+
+                // Keep the keywords that are only annotations, so `to_value`
+                // can write them back out.
+                if let Some(val) = obj.get("examples") {
+                    if !val.is_array() {
+                        return Err(FromValueError::InvalidKeywordType(json.clone(), "examples".to_string(), val.clone()));
+                    }
+                }
+                let annotations = ["$schema", "default", "examples"].iter()
+                    .filter_map(|&k| obj.get(k).map(|v| (k.to_string(), v.clone())))
+                    .collect::<Map<_, _>>();
     
+                // Register the `definitions`. This is done even if the schema
+                // is a `$ref`, since the `$ref` may point into them.
+                match obj.get("definitions") {
+                    Some(&Value::Object(ref defs)) => for (name, def) in defs {
+                        let uri = push_uri(push_uri(id.clone(), "definitions".to_string()), name.to_string());
+                        self.parse(uri, def, depth + 1)?;
+                    },
+                    Some(val) => return Err(FromValueError::InvalidKeywordType(json.clone(), "definitions".to_string(), val.clone())),
+                    None => {},
+                }
+
                 // Check if this schema is a `$ref`.
                 // N.B. Infinitely recursive schema are undefined behavior by
                 // the spec, but it might be nice to allow them. This resolves
@@ -72,7 +142,7 @@ impl Context {
                         let r = id.join(r).map_err(|_| {
                             FromValueError::InvalidKeywordValue(json.clone(), "$ref".to_string(), val.clone())
                         })?;
-                        (Validator::Reference(r.to_owned()), id, title, description)
+                        (Validator::Reference(r.to_owned()), id, title, description, format, annotations)
                     } else {
                         return Err(FromValueError::InvalidKeywordType(json.clone(), "$ref".to_string(), val.clone()));
                     }
@@ -82,28 +152,34 @@ impl Context {
                     // Process the items and additionalItems fields.
                     if let Some(val) = obj.get("items") {
                         let uri = push_uri(id.clone(), "items".to_string());
-                        conditions.push(if let Value::Array(ref arr) = *val {
-                            let items = arr.iter().enumerate().map(|(i, s)| {
-                                let uri = push_uri(uri.clone(), i.to_string());
-                                self.parse(uri, s, depth + 1)
-                            }).collect::<Result<Vec<_>, _>>()?;
-                            let additional_items = if let Some(val) = obj.get("additionalItems") {
-                                let uri = push_uri(id.clone(), "additionalItems".to_string());
-                                Some(self.parse(uri, val, depth + 1)?)
-                            } else {
-                                None
-                            };
-                            Condition::Items(items, additional_items)
-                        } else {
-                            let items = self.parse(uri, val, depth + 1)?;
-                            Condition::Items(Vec::new(), Some(items))
+                        conditions.push(match *val {
+                            Value::Array(ref arr) => {
+                                let items = arr.iter().enumerate().map(|(i, s)| {
+                                    let uri = push_uri(uri.clone(), i.to_string());
+                                    self.parse(uri, s, depth + 1)
+                                }).collect::<Result<Vec<_>, _>>()?;
+                                let additional_items = match obj.get("additionalItems") {
+                                    Some(val @ &Value::Bool(_)) | Some(val @ &Value::Object(_)) => {
+                                        let uri = push_uri(id.clone(), "additionalItems".to_string());
+                                        Some(self.parse(uri, val, depth + 1)?)
+                                    },
+                                    Some(val) => return Err(FromValueError::InvalidKeywordType(json.clone(), "additionalItems".to_string(), val.clone())),
+                                    None => None,
+                                };
+                                Condition::Items(items, additional_items)
+                            },
+                            Value::Bool(_) | Value::Object(_) => {
+                                let items = self.parse(uri, val, depth + 1)?;
+                                Condition::Items(Vec::new(), Some(items))
+                            },
+                            _ => return Err(FromValueError::InvalidKeywordType(json.clone(), "items".to_string(), val.clone())),
                         })
                     }
 
                     // Process the properties, patternProperties, and additionalProperties fields.
                     let properties = match obj.get("properties") {
                         Some(&Value::Object(ref obj)) => Some(obj.iter().map(|(k, v)| {
-                            let uri = push_uri(id.clone(), k.to_string());
+                            let uri = push_uri(push_uri(id.clone(), "properties".to_string()), k.to_string());
                             self.parse(uri, v, depth + 1)
                                 .map(|u| (k.to_owned(), u))
                         }).collect::<Result<_, _>>()?),
@@ -112,7 +188,7 @@ impl Context {
                     };
                     let pattern_properties = match obj.get("patternProperties") {
                         Some(&Value::Object(ref obj)) => Some(obj.iter().map(|(k, v)| {
-                            let uri = push_uri(id.clone(), k.to_string());
+                            let uri = push_uri(push_uri(id.clone(), "patternProperties".to_string()), k.to_string());
                             self.parse(uri, v, depth + 1).and_then(|u| {
                                 match k.parse() {
                                     Ok(re) => Ok((RegexWrapper(re), u)),
@@ -143,7 +219,8 @@ impl Context {
                                     let uri = push_uri(push_uri(id.clone(), "allOf".to_string()), format!("{}", i));
                                     self.parse(uri, v, depth + 1)
                                 }).collect::<Result<Vec<_>, _>>()?;
-                                conditions.push(Condition::AllOf(schemas));
+                                let nothing = nothing_branch(self, &schemas);
+                                conditions.push(Condition::AllOf(schemas, nothing));
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
@@ -160,52 +237,161 @@ impl Context {
                             "contains" => {
                                 let uri = push_uri(id.clone(), "contains".to_string());
                                 let uri = self.parse(uri, v, depth + 1)?;
-                                conditions.push(Condition::Contains(uri))
+                                #[cfg(feature = "contains-count")]
+                                let (min, max) = (parse_contains_count(json, obj, "minContains")?,
+                                    parse_contains_count(json, obj, "maxContains")?);
+                                #[cfg(not(feature = "contains-count"))]
+                                let (min, max) = (None, None);
+                                conditions.push(Condition::Contains(uri, min, max))
                             },
-                            "exclusiveMaximum" => if let Value::Number(ref n) = *v {
-                                conditions.push(Condition::ExclusiveMaximum(n.clone()));
+                            "dependencies" => if let Value::Object(ref deps) = *v {
+                                let deps = deps.iter().map(|(name, dep)| {
+                                    let dep = if let Value::Array(ref arr) = *dep {
+                                        let props = arr.iter().map(|p| match *p {
+                                            Value::String(ref s) => Ok(s.to_string()),
+                                            _ => Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone())),
+                                        }).collect::<Result<Vec<_>, _>>()?;
+                                        let mut seen = BTreeSet::new();
+                                        if !props.iter().all(|p| seen.insert(p)) {
+                                            return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                        }
+                                        Either::Left(props)
+                                    } else {
+                                        let uri = push_uri(push_uri(id.clone(), "dependencies".to_string()), name.to_string());
+                                        Either::Right(self.parse(uri, dep, depth + 1)?)
+                                    };
+                                    Ok((name.to_owned(), dep))
+                                }).collect::<Result<_, _>>()?;
+                                conditions.push(Condition::Dependencies(deps));
+                            } else {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
+                            "enum" => if let Value::Array(ref arr) = *v {
+                                conditions.push(Condition::Enum(arr.clone()));
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
-                            "exclusiveMinimum" => if let Value::Number(ref n) = *v {
-                                conditions.push(Condition::ExclusiveMinimum(n.clone()));
+                            "exclusiveMaximum" => match *v {
+                                Value::Number(ref n) => conditions.push(Condition::ExclusiveMaximum(n.clone())),
+                                Value::Bool(_) if self.draft4_exclusive_bounds => {}, // Handled with `maximum`.
+                                _ => return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone())),
+                            },
+                            "exclusiveMinimum" => match *v {
+                                Value::Number(ref n) => conditions.push(Condition::ExclusiveMinimum(n.clone())),
+                                Value::Bool(_) if self.draft4_exclusive_bounds => {}, // Handled with `minimum`.
+                                _ => return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone())),
+                            },
+                            #[cfg(feature = "draft7")]
+                            "if" => {
+                                let cond = self.parse(push_uri(id.clone(), "if".to_string()), v, depth + 1)?;
+                                let then = match obj.get("then") {
+                                    Some(v) => Some(self.parse(push_uri(id.clone(), "then".to_string()), v, depth + 1)?),
+                                    None => None,
+                                };
+                                let else_ = match obj.get("else") {
+                                    Some(v) => Some(self.parse(push_uri(id.clone(), "else".to_string()), v, depth + 1)?),
+                                    None => None,
+                                };
+                                conditions.push(Condition::If(cond, then, else_));
+                            },
+                            "maxItems" => if let Value::Number(ref n) = *v {
+                                if let Some(n) = number_as_u64(n) {
+                                    conditions.push(Condition::MaxItems(n));
+                                } else {
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                }
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
                             "maxLength" => if let Value::Number(ref n) = *v {
-                                if let Some(n) = n.as_u64() {
+                                if let Some(n) = number_as_u64(n) {
                                     conditions.push(Condition::MaxLength(n));
                                 } else {
-                                    return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                }
+                            } else {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
+                            "maxProperties" => if let Value::Number(ref n) = *v {
+                                if let Some(n) = number_as_u64(n) {
+                                    conditions.push(Condition::MaxProperties(n));
+                                } else {
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
                                 }
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
                             "maximum" => if let Value::Number(ref n) = *v {
-                                conditions.push(Condition::Maximum(n.clone()));
+                                let exclusive = obj.get("exclusiveMaximum") == Some(&Value::Bool(true));
+                                if self.draft4_exclusive_bounds && exclusive {
+                                    conditions.push(Condition::ExclusiveMaximum(n.clone()));
+                                } else {
+                                    conditions.push(Condition::Maximum(n.clone()));
+                                }
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
                             "minItems" => if let Value::Number(ref n) = *v {
-                                if let Some(n) = n.as_u64() {
+                                if let Some(n) = number_as_u64(n) {
                                     conditions.push(Condition::MinItems(n));
                                 } else {
-                                    return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
                                 }
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
                             "minLength" => if let Value::Number(ref n) = *v {
-                                if let Some(n) = n.as_u64() {
+                                if let Some(n) = number_as_u64(n) {
                                     conditions.push(Condition::MinLength(n));
                                 } else {
-                                    return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                }
+                            } else {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
+                            "minProperties" => if let Value::Number(ref n) = *v {
+                                if let Some(n) = number_as_u64(n) {
+                                    conditions.push(Condition::MinProperties(n));
+                                } else {
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
                                 }
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
                             "minimum" => if let Value::Number(ref n) = *v {
-                                conditions.push(Condition::Minimum(n.clone()));
+                                let exclusive = obj.get("exclusiveMinimum") == Some(&Value::Bool(true));
+                                if self.draft4_exclusive_bounds && exclusive {
+                                    conditions.push(Condition::ExclusiveMinimum(n.clone()));
+                                } else {
+                                    conditions.push(Condition::Minimum(n.clone()));
+                                }
+                            } else {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
+                            "multipleOf" => match *v {
+                                Value::Number(ref n) if n.as_f64().map(|n| n > 0.0).unwrap_or(false) => {
+                                    conditions.push(Condition::MultipleOf(n.clone()));
+                                },
+                                Value::Number(_) => {
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                },
+                                _ => return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone())),
+                            },
+                            "not" => {
+                                let uri = push_uri(id.clone(), "not".to_string());
+                                let uri = self.parse(uri, v, depth + 1)?;
+                                conditions.push(Condition::Not(uri))
+                            },
+                            "oneOf" => if let Value::Array(ref arr) = *v {
+                                let schemas = arr.into_iter().enumerate().map(|(i, v)| {
+                                    let uri = push_uri(push_uri(id.clone(), "oneOf".to_string()), format!("{}", i));
+                                    self.parse(uri, v, depth + 1)
+                                }).collect::<Result<Vec<_>, _>>()?;
+                                let discriminator = match obj.get("discriminator") {
+                                    Some(d) => Some(parse_discriminator(json, &id, d)?),
+                                    None => None,
+                                };
+                                conditions.push(Condition::OneOf(schemas, discriminator));
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
@@ -215,6 +401,11 @@ impl Context {
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
+                            "propertyNames" => {
+                                let uri = push_uri(id.clone(), "propertyNames".to_string());
+                                let uri = self.parse(uri, v, depth + 1)?;
+                                conditions.push(Condition::PropertyNames(uri))
+                            },
                             "required" => if let Value::Array(ref arr) = *v {
                                 let mut required = Vec::new();
                                 for v in arr {
@@ -228,7 +419,17 @@ impl Context {
                             } else {
                                 return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
                             },
+                            "uniqueItems" => if let Value::Bool(b) = *v {
+                                conditions.push(Condition::UniqueItems(b));
+                            } else {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
                             "type" => match *v {
+                                // An empty list would reject every value,
+                                // and the metaschema forbids it anyway.
+                                Value::Array(ref arr) if arr.is_empty() => {
+                                    return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                },
                                 Value::Array(ref arr) => {
                                     let types = arr.into_iter().map(|vv| {
                                         if let Value::String(ref ty) = *vv {
@@ -239,7 +440,19 @@ impl Context {
                                             Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()))
                                         }
                                     }).collect::<Result<Vec<_>, _>>()?;
-                                    conditions.push(Condition::Type(types))
+
+                                    // The spec requires the types to be
+                                    // unique, but a repeat is harmless, so
+                                    // it's only an error in strict mode.
+                                    let mut unique = Vec::with_capacity(types.len());
+                                    for ty in types {
+                                        if !unique.contains(&ty) {
+                                            unique.push(ty);
+                                        } else if self.strict_keywords {
+                                            return Err(FromValueError::InvalidKeywordValue(json.clone(), k.clone(), v.clone()));
+                                        }
+                                    }
+                                    conditions.push(Condition::Type(unique))
                                 },
                                 Value::String(ref ty) => {
                                     let ty = Type::from_string(ty).ok_or_else(|| {
@@ -254,40 +467,113 @@ impl Context {
                             // Intentionally ignored fields
                             "additionalItems" | "items" => {},
                             "additionalProperties" | "patternProperties" | "properties" => {},
-                            "definitions" => {}, // TODO
-                            "$schema" | "$ref" | "$id" | "title" | "description" => {}, // Already checked for.
-                            "default" | "examples" => {}, // We don't validate these.
-                            "format" => {}, // TODO Eventually...
-                            // Not implemented or not-in-spec fields
-                            _ => {
-                                println!("NYI field {}", k);
-                                unimplemented!();
-                            }
+                            "definitions" => {}, // Already registered.
+                            "discriminator" if obj.contains_key("oneOf") => {}, // Handled with `oneOf`.
+                            #[cfg(feature = "contains-count")]
+                            "minContains" | "maxContains" => {
+                                // Handled with `contains`, but checked even
+                                // without it.
+                                parse_contains_count(json, obj, k)?;
+                            },
+                            "$schema" | "$ref" | "$id" | "title" | "description" | "format" => {}, // Already checked for.
+                            "default" | "examples" => {}, // Already stored as annotations.
+                            #[cfg(feature = "draft7")]
+                            "then" | "else" => {}, // Handled with `if`.
+                            #[cfg(feature = "draft7")]
+                            "readOnly" | "writeOnly" => if !v.is_boolean() {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
+                            #[cfg(feature = "draft7")]
+                            "$comment" | "contentEncoding" | "contentMediaType" => if !v.is_string() {
+                                return Err(FromValueError::InvalidKeywordType(json.clone(), k.clone(), v.clone()));
+                            },
+                            // Not-in-spec fields
+                            _ => if self.strict_keywords {
+                                return Err(FromValueError::UnknownKeyword(json.clone(), k.clone()));
+                            } else {
+                                diagnostics.push(Diagnostic::UnknownKeyword(k.clone()));
+                            },
                         }
                     }
                     conditions.sort_by_key(|c| c.priority());
-                    (Validator::Conditions(conditions), id, title, description)
+                    (Validator::Conditions(conditions), id, title, description, format, annotations)
                 }
             },
             _ => return Err(FromValueError::InvalidSchemaType(json.clone())),
         };
+        let reusable = dedup_key.filter(|_| validator.subschemas().is_empty());
         self.put(id.clone(), JsonSchemaInner {
+            annotations,
             description,
+            diagnostics,
+            format,
             title,
             validator,
         });
+        if let Some(key) = reusable {
+            self.dedup_record(id.clone(), key);
+        }
+        if depth == 0 {
+            self.dedup_clear();
+        }
         Ok(id)
     }
 }
 
+/// Parses the `discriminator` extension keyword of the schema `json`, which
+/// has the URI `id`.
+fn parse_discriminator(json: &Value, id: &Url, v: &Value) -> Result<Discriminator, FromValueError> {
+    let invalid = || FromValueError::InvalidKeywordValue(json.clone(), "discriminator".to_string(), v.clone());
+    let obj = match *v {
+        Value::Object(ref obj) => obj,
+        _ => return Err(FromValueError::InvalidKeywordType(json.clone(), "discriminator".to_string(), v.clone())),
+    };
+    let property_name = match obj.get("propertyName") {
+        Some(&Value::String(ref name)) => name.to_owned(),
+        _ => return Err(invalid()),
+    };
+    let mapping = match obj.get("mapping") {
+        Some(&Value::Object(ref mapping)) => mapping.iter().map(|(k, v)| match *v {
+            Value::String(ref r) => id.join(r).map(|uri| (k.to_owned(), uri)).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }).collect::<Result<_, _>>()?,
+        Some(_) => return Err(invalid()),
+        None => BTreeMap::new(),
+    };
+    Ok(Discriminator { property_name, mapping })
+}
+
+/// Parses the `minContains` or `maxContains` keyword `k` of the schema `json`,
+/// returning `None` if it is absent.
+#[cfg(feature = "contains-count")]
+fn parse_contains_count(json: &Value, obj: &Map<String, Value>, k: &str) -> Result<Option<u64>, FromValueError> {
+    match obj.get(k) {
+        Some(&Value::Number(ref n)) => number_as_u64(n).map(Some).ok_or_else(|| {
+            FromValueError::InvalidKeywordValue(json.clone(), k.to_string(), Value::Number(n.clone()))
+        }),
+        Some(v) => Err(FromValueError::InvalidKeywordType(json.clone(), k.to_string(), v.clone())),
+        None => Ok(None),
+    }
+}
+
 /// Pushes a new component to the JSON pointer in the fragment portion of a
 /// URI. If the fragment is not present or not a JSON pointer, overrides it.
+///
+/// The component is escaped as per [Section 4 of RFC
+/// 6901](https://tools.ietf.org/html/rfc6901#section-4), then percent-encoded
+/// as per [Section 6](https://tools.ietf.org/html/rfc6901#section-6), so that
+/// a `$ref` written to the spec resolves to it.
 fn push_uri(mut uri: Url, component: String) -> Url {
-    let mut ptr = uri.fragment().and_then(|f| {
-        f.parse::<JsonPointer<_, _>>().ok()
-    }).unwrap_or_else(|| "/".parse().unwrap());
-    ptr.push(component);
+    let mut fragment = match uri.fragment() {
+        Some(f) if f.starts_with('/') => f.to_string(),
+        _ => String::new(),
+    };
+    let component = component.replace('~', "~0")
+        .replace('/', "~1")
+        .replace('%', "%25");
+    fragment.push('/');
+    fragment.extend(utf8_percent_encode(&component, DEFAULT_ENCODE_SET));
 
-    uri.set_fragment(Some(&ptr.to_string()));
+    uri.set_fragment(Some(&fragment));
     uri
 }
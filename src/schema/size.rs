@@ -0,0 +1,136 @@
+use either::Either;
+use lint::Diagnostic;
+use serde_json::{Map, Value};
+use std::mem::size_of;
+use super::{Condition, Discriminator, JsonSchemaInner, RegexWrapper, Validator};
+use url::Url;
+
+/// The assumed ratio of a compiled regex's size to the length of its source,
+/// since the real size isn't exposed.
+const REGEX_SIZE_FACTOR: usize = 64;
+
+impl JsonSchemaInner {
+    pub(crate) fn estimated_size(&self) -> usize {
+        size_of::<JsonSchemaInner>() +
+            self.title.as_ref().map(|s| s.len()).unwrap_or(0) +
+            self.description.as_ref().map(|s| s.len()).unwrap_or(0) +
+            self.format.as_ref().map(|s| s.len()).unwrap_or(0) +
+            self.diagnostics.iter().map(|d| size_of::<Diagnostic>() + d.heap_size()).sum::<usize>() +
+            map_heap_size(&self.annotations) +
+            self.validator.heap_size()
+    }
+}
+
+impl Diagnostic {
+    fn heap_size(&self) -> usize {
+        match *self {
+            Diagnostic::FormatNotEnforced(ref s) | Diagnostic::UnknownKeyword(ref s) => s.len(),
+        }
+    }
+}
+
+impl Validator {
+    fn heap_size(&self) -> usize {
+        match *self {
+            Validator::Anything | Validator::Nothing => 0,
+            Validator::Conditions(ref c) => c.iter()
+                .map(|c| size_of::<Condition>() + c.heap_size())
+                .sum(),
+            Validator::Reference(ref r) => url_heap_size(r),
+        }
+    }
+}
+
+impl Condition {
+    fn heap_size(&self) -> usize {
+        match *self {
+            Condition::MultipleOf(..) |
+            Condition::Maximum(..) |
+            Condition::ExclusiveMaximum(..) |
+            Condition::Minimum(..) |
+            Condition::ExclusiveMinimum(..) |
+            Condition::MaxLength(..) |
+            Condition::MinLength(..) |
+            Condition::MaxItems(..) |
+            Condition::MinItems(..) |
+            Condition::UniqueItems(..) |
+            Condition::MaxProperties(..) |
+            Condition::MinProperties(..) => 0,
+            Condition::Pattern(ref re) => re.as_str().len() * REGEX_SIZE_FACTOR,
+            Condition::Items(ref items, ref additional) => urls_heap_size(items) +
+                additional.as_ref().map(url_heap_size).unwrap_or(0),
+            Condition::Contains(ref url, _, _) |
+            Condition::PropertyNames(ref url) |
+            Condition::Not(ref url) => url_heap_size(url),
+            Condition::Required(ref names) => names.iter()
+                .map(|s| size_of::<String>() + s.len())
+                .sum(),
+            Condition::Properties(ref props, ref patterns, ref additional) => {
+                let props = props.iter()
+                    .map(|(k, v)| size_of::<(String, Url)>() + k.len() + url_heap_size(v))
+                    .sum::<usize>();
+                let patterns = patterns.iter()
+                    .map(|(k, v)| size_of::<(RegexWrapper, Url)>() + k.as_str().len() * REGEX_SIZE_FACTOR + url_heap_size(v))
+                    .sum::<usize>();
+                props + patterns + additional.as_ref().map(url_heap_size).unwrap_or(0)
+            },
+            Condition::Dependencies(ref deps) => deps.iter()
+                .map(|(k, v)| {
+                    let v = match *v {
+                        Either::Left(ref names) => names.iter()
+                            .map(|s| size_of::<String>() + s.len())
+                            .sum(),
+                        Either::Right(ref url) => url_heap_size(url),
+                    };
+                    size_of::<(String, Either<Vec<String>, Url>)>() + k.len() + v
+                })
+                .sum(),
+            Condition::Enum(ref values) => values.iter()
+                .map(|v| size_of::<Value>() + value_heap_size(v))
+                .sum(),
+            Condition::Const(ref value) => value_heap_size(value),
+            Condition::Type(ref types) => types.len(),
+            Condition::AllOf(ref urls, _) |
+            Condition::AnyOf(ref urls) => urls_heap_size(urls),
+            Condition::OneOf(ref urls, ref discriminator) => urls_heap_size(urls) +
+                discriminator.as_ref().map(|d| {
+                    let mapping = d.mapping.iter()
+                        .map(|(k, v)| size_of::<(String, Url)>() + k.len() + url_heap_size(v))
+                        .sum::<usize>();
+                    size_of::<Discriminator>() + d.property_name.len() + mapping
+                }).unwrap_or(0),
+            #[cfg(feature = "draft7")]
+            Condition::If(ref cond, ref then, ref else_) => url_heap_size(cond) +
+                then.as_ref().map(url_heap_size).unwrap_or(0) +
+                else_.as_ref().map(url_heap_size).unwrap_or(0),
+        }
+    }
+}
+
+/// Returns the heap memory owned by a URI.
+pub(crate) fn url_heap_size(url: &Url) -> usize {
+    url.as_str().len()
+}
+
+fn urls_heap_size(urls: &[Url]) -> usize {
+    urls.iter()
+        .map(|url| size_of::<Url>() + url_heap_size(url))
+        .sum()
+}
+
+fn map_heap_size(map: &Map<String, Value>) -> usize {
+    map.iter()
+        .map(|(k, v)| size_of::<(String, Value)>() + k.len() + value_heap_size(v))
+        .sum()
+}
+
+fn value_heap_size(value: &Value) -> usize {
+    match *value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+        Value::String(ref s) => s.len(),
+        Value::Array(ref arr) => arr.iter()
+            .map(|v| size_of::<Value>() + value_heap_size(v))
+            .sum(),
+        Value::Object(ref obj) => map_heap_size(obj),
+    }
+}
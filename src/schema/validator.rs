@@ -1,6 +1,9 @@
+use annotations::Annotations;
 use errors::ValidationError;
 use serde_json::{Map, Value};
-use super::{Condition, Context};
+use std::cell::RefCell;
+use super::{Condition, Context, JsonSchema};
+use tree::ErrorTree;
 use url::Url;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -19,8 +22,62 @@ pub enum Validator {
 }
 
 impl Validator {
-    pub fn to_json_object(&self) -> Map<String, Value> {
-        unimplemented!()
+    /// Returns the URIs of all the schemas this validator refers to.
+    pub fn subschemas(&self) -> Vec<&Url> {
+        match *self {
+            Validator::Conditions(ref c) => c.iter()
+                .flat_map(|c| c.subschemas())
+                .collect(),
+            Validator::Reference(ref r) => vec![r],
+            Validator::Anything | Validator::Nothing => Vec::new(),
+        }
+    }
+
+    /// Returns whether two validators check the same things, ignoring the
+    /// order of their conditions.
+    pub(crate) fn equivalent(&self, other: &Validator) -> bool {
+        match (self, other) {
+            (&Validator::Conditions(ref a), &Validator::Conditions(ref b)) => {
+                a.len() == b.len() && a.iter().all(|c| b.contains(c))
+            },
+            (&Validator::Conditions(ref c), &Validator::Anything) |
+            (&Validator::Anything, &Validator::Conditions(ref c)) => c.is_empty(),
+            (a, b) => a == b,
+        }
+    }
+
+    /// Coerces strings in the value to the types the schema asks for, as
+    /// described in `Context::set_coerce_types`.
+    pub(crate) fn coerce(&self, ctx: &Context, json: &mut Value) {
+        match *self {
+            Validator::Conditions(ref c) => for c in c {
+                c.coerce(ctx, json);
+            },
+            Validator::Reference(ref r) => {
+                let key = &*json as *const Value;
+                let _ = follow(ctx, r, key, |schema| schema.inner.validator.coerce(ctx, json));
+            },
+            Validator::Anything | Validator::Nothing => {},
+        }
+    }
+
+    /// Returns the keywords of the schema with the given URI that this
+    /// validator corresponds to.
+    pub fn to_json_object(&self, ctx: &Context, id: &Url) -> Map<String, Value> {
+        let mut map = Map::new();
+        match *self {
+            Validator::Anything => {},
+            Validator::Conditions(ref c) => for c in c {
+                map.extend(c.to_pairs(ctx, id));
+            },
+            Validator::Nothing => {
+                map.insert("not".to_string(), Value::Object(Map::new()));
+            },
+            Validator::Reference(ref r) => {
+                map.insert("$ref".to_string(), Value::String(r.to_string()));
+            },
+        }
+        map
     }
 
     pub fn validate(&self, ctx: &Context, json: &Value) -> Result<(), ValidationError> {
@@ -30,12 +87,124 @@ impl Validator {
                 .map(|c| c.validate(ctx, json))
                 .collect::<Result<Vec<_>, _>>().map(|_| ()),
             Validator::Nothing => Err(ValidationError::NoValuesPass(json.clone())),
-            Validator::Reference(ref r) => if let Some(schema) = ctx.get(r) {
-                // TODO Check for self-referential schema?
-                schema.validate(json)
-            } else {
-                Err(ValidationError::BadReference(r.clone()))
+            Validator::Reference(ref r) => follow(ctx, r, json, |schema| schema.validate(json))?,
+        }
+    }
+
+    /// Returns whether the value passes, without constructing any errors, or
+    /// `None` if `validate` would return a resolution error. As with
+    /// `validate`, the first condition that doesn't pass decides which.
+    pub(crate) fn check(&self, ctx: &Context, json: &Value) -> Option<bool> {
+        match *self {
+            Validator::Anything => Some(true),
+            Validator::Conditions(ref c) => check_all(c.iter().map(|c| c.check(ctx, json))),
+            Validator::Nothing => Some(false),
+            Validator::Reference(ref r) => follow(ctx, r, json, |schema| schema.check(json))
+                .unwrap_or(None),
+        }
+    }
+
+    /// Adds the errors from validating the value to the tree, as described in
+    /// `JsonSchema::validate_tree`.
+    pub(crate) fn validate_tree(&self, ctx: &Context, json: &Value, tree: &mut ErrorTree) {
+        match *self {
+            Validator::Anything => {},
+            Validator::Conditions(ref c) => for c in c {
+                c.validate_tree(ctx, json, tree);
             },
+            Validator::Nothing => tree.push(ValidationError::NoValuesPass(json.clone())),
+            Validator::Reference(ref r) => {
+                let result = follow(ctx, r, json, |schema| schema.inner.validator.validate_tree(ctx, json, tree));
+                if let Err(err) = result {
+                    tree.push(err);
+                }
+            },
+        }
+    }
+
+    /// Adds the annotations from a valid value, as described in
+    /// `JsonSchema::validate_annotated`.
+    pub(crate) fn annotate(&self, ctx: &Context, json: &Value, path: &mut Vec<String>, out: &mut Annotations) {
+        match *self {
+            Validator::Anything | Validator::Nothing => {},
+            Validator::Conditions(ref c) => for c in c {
+                c.annotate(ctx, json, path, out);
+            },
+            Validator::Reference(ref r) => {
+                let _ = follow(ctx, r, json, |schema| schema.annotate(json, path, out));
+            },
+        }
+    }
+
+    pub fn validate_all(&self, ctx: &Context, json: &Value) -> Vec<ValidationError> {
+        match *self {
+            Validator::Anything => Vec::new(),
+            Validator::Conditions(ref c) => c.iter()
+                .filter_map(|c| c.validate(ctx, json).err())
+                .collect(),
+            Validator::Nothing => vec![ValidationError::NoValuesPass(json.clone())],
+            Validator::Reference(ref r) => follow(ctx, r, json, |schema| schema.inner.validator.validate_all(ctx, json))
+                .unwrap_or_else(|err| vec![err]),
+        }
+    }
+
+    /// Passes each error from validating the value to the sink, as described
+    /// in `JsonSchema::validate_each`. Returns false if the sink asked to
+    /// stop.
+    pub(crate) fn validate_each(&self, ctx: &Context, json: &Value, sink: &mut dyn FnMut(ValidationError) -> bool) -> bool {
+        match *self {
+            Validator::Anything => true,
+            Validator::Conditions(ref c) => c.iter().all(|c| c.validate_each(ctx, json, sink)),
+            Validator::Nothing => sink(ValidationError::NoValuesPass(json.clone())),
+            Validator::Reference(ref r) => follow(ctx, r, json, |schema| schema.inner.validator.validate_each(ctx, json, sink))
+                .unwrap_or_else(sink),
+        }
+    }
+}
+
+thread_local! {
+    /// The references being followed on this thread, each with the value it's
+    /// being followed for. Recursing into a subschema either descends into the
+    /// value or doesn't, and the value is finite, so the only way for
+    /// validation not to end is to follow the same reference for the same
+    /// value again.
+    static FOLLOWING: RefCell<Vec<(*const Url, *const Value)>> = RefCell::new(Vec::new());
+}
+
+/// Calls `f` with the schema the reference refers to. Returns a
+/// `BadReference` error if there's no such schema, or a `ReferenceCycle`
+/// error if the reference is already being followed for the same value.
+fn follow<'a, T, F: FnOnce(JsonSchema<'a>) -> T>(ctx: &'a Context, r: &Url, json: *const Value, f: F) -> Result<T, ValidationError> {
+    let schema = ctx.try_get(r)?;
+    let key = (r as *const Url, json);
+    let cycle = FOLLOWING.with(|following| {
+        let mut following = following.borrow_mut();
+        if following.contains(&key) {
+            true
+        } else {
+            following.push(key);
+            false
         }
+    });
+    if cycle {
+        return Err(ValidationError::ReferenceCycle(r.clone()));
     }
+    let _unfollow = Unfollow;
+    Ok(f(schema))
+}
+
+/// Removes the reference `follow` added once it's done, even if `f` panicked.
+struct Unfollow;
+
+impl Drop for Unfollow {
+    fn drop(&mut self) {
+        FOLLOWING.with(|following| following.borrow_mut().pop());
+    }
+}
+
+/// Combines the results of `check`s made in order, the way `validate` combines
+/// them with `?`: the first one that doesn't pass decides the result. Later
+/// ones aren't made.
+pub(crate) fn check_all<I: IntoIterator<Item = Option<bool>>>(checks: I) -> Option<bool> {
+    checks.into_iter().find(|&c| c != Some(true)).unwrap_or(Some(true))
 }
@@ -0,0 +1,91 @@
+use errors::ValidationError;
+use schema::Context;
+use std::io::{BufRead, ErrorKind};
+use url::Url;
+
+/// An iterator over the results of validating newline-delimited JSON, one
+/// value per line. Created by
+/// [`JsonSchema::validate_reader`](struct.JsonSchema.html#method.validate_reader).
+///
+/// Blank lines are skipped. A line that isn't valid JSON yields an
+/// `InvalidJson` error, and validation continues with the next line. An I/O
+/// error yields an `Io` error, and ends the iteration.
+#[derive(Debug)]
+pub struct ValidateReader<'a, R> {
+    ctx: &'a Context,
+    done: bool,
+    line: usize,
+    reader: R,
+    uri: Url,
+}
+
+impl<'a, R: BufRead> ValidateReader<'a, R> {
+    pub(crate) fn new(ctx: &'a Context, uri: Url, reader: R) -> ValidateReader<'a, R> {
+        ValidateReader {
+            ctx,
+            done: false,
+            line: 0,
+            reader,
+            uri,
+        }
+    }
+
+    /// Returns the next result, along with the (1-based) number of the line
+    /// it came from.
+    fn next_numbered(&mut self) -> Option<(usize, Result<(), ValidationError>)> {
+        while !self.done {
+            let mut line = String::new();
+            let result = self.reader.read_line(&mut line);
+            self.line += 1;
+            match result {
+                Ok(0) => self.done = true,
+                Ok(_) => if !line.trim().is_empty() {
+                    return Some((self.line, match ::serde_json::from_str(&line) {
+                        Ok(json) => self.ctx.validate(&self.uri, &json),
+                        Err(err) => Err(ValidationError::InvalidJson(err.to_string())),
+                    }));
+                },
+                Err(ref err) if err.kind() == ErrorKind::InvalidData => {
+                    return Some((self.line, Err(ValidationError::InvalidJson(err.to_string()))));
+                },
+                Err(err) => {
+                    self.done = true;
+                    return Some((self.line, Err(ValidationError::Io(err.to_string()))));
+                },
+            }
+        }
+        None
+    }
+}
+
+impl<'a, R: BufRead> Iterator for ValidateReader<'a, R> {
+    type Item = Result<(), ValidationError>;
+
+    fn next(&mut self) -> Option<Result<(), ValidationError>> {
+        self.next_numbered().map(|(_, result)| result)
+    }
+}
+
+/// An iterator over the results of validating newline-delimited JSON, along
+/// with the (1-based) line number each came from. Created by
+/// [`Context::validate_ndjson`](struct.Context.html#method.validate_ndjson).
+///
+/// This behaves like [`ValidateReader`](struct.ValidateReader.html), except
+/// for the line numbers. Blank lines are still counted, so the numbers match
+/// the input.
+#[derive(Debug)]
+pub struct ValidateNdjson<'a, R>(ValidateReader<'a, R>);
+
+impl<'a, R: BufRead> ValidateNdjson<'a, R> {
+    pub(crate) fn new(ctx: &'a Context, uri: Url, reader: R) -> ValidateNdjson<'a, R> {
+        ValidateNdjson(ValidateReader::new(ctx, uri, reader))
+    }
+}
+
+impl<'a, R: BufRead> Iterator for ValidateNdjson<'a, R> {
+    type Item = (usize, Result<(), ValidationError>);
+
+    fn next(&mut self) -> Option<(usize, Result<(), ValidationError>)> {
+        self.0.next_numbered()
+    }
+}
@@ -0,0 +1,83 @@
+use errors::ValidationError;
+use json_pointer::JsonPointer;
+use std::vec::IntoIter;
+
+/// The errors from validating a value, arranged by where in the value they
+/// occurred. Created by
+/// [`JsonSchema::validate_tree`](struct.JsonSchema.html#method.validate_tree).
+///
+/// Iterating over the tree yields each error along with a JSON pointer to the
+/// value it occurred at, in document order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ErrorTree {
+    errors: Vec<ValidationError>,
+    children: Vec<(String, ErrorTree)>,
+}
+
+impl ErrorTree {
+    /// Returns the number of errors in the tree.
+    pub fn len(&self) -> usize {
+        self.errors.len() + self.children.iter()
+            .map(|&(_, ref child)| child.len())
+            .sum::<usize>()
+    }
+
+    /// Returns whether the tree has no errors, i.e. whether the value was
+    /// valid.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.children.is_empty()
+    }
+
+    /// Returns each error along with the JSON pointer to the value it occurred
+    /// at, as a string.
+    pub fn to_flat_vec(&self) -> Vec<(String, ValidationError)> {
+        self.clone().into_iter()
+            .map(|(path, err)| (path.to_string(), err))
+            .collect()
+    }
+
+    /// Adds an error at the root of the tree.
+    pub(crate) fn push(&mut self, err: ValidationError) {
+        self.errors.push(err);
+    }
+
+    /// Adds the errors for the child value with the given property name or
+    /// array index, if there are any.
+    pub(crate) fn push_child(&mut self, key: String, child: ErrorTree) {
+        if child.is_empty() {
+            return;
+        }
+        // A value checked against several subschemas in a row (e.g. both
+        // `properties` and `patternProperties`) gets a single entry.
+        if let Some(&mut (ref last, ref mut tree)) = self.children.last_mut() {
+            if *last == key {
+                tree.errors.extend(child.errors);
+                tree.children.extend(child.children);
+                return;
+            }
+        }
+        self.children.push((key, child));
+    }
+
+    fn flatten(self, path: &mut Vec<String>, out: &mut Vec<(JsonPointer<String, Vec<String>>, ValidationError)>) {
+        for err in self.errors {
+            out.push((JsonPointer::new(path.clone()), err));
+        }
+        for (key, child) in self.children {
+            path.push(key);
+            child.flatten(path, out);
+            path.pop();
+        }
+    }
+}
+
+impl IntoIterator for ErrorTree {
+    type Item = (JsonPointer<String, Vec<String>>, ValidationError);
+    type IntoIter = IntoIter<(JsonPointer<String, Vec<String>>, ValidationError)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut out = Vec::with_capacity(self.len());
+        self.flatten(&mut Vec::new(), &mut out);
+        out.into_iter()
+    }
+}
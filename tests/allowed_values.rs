@@ -0,0 +1,53 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::Context;
+use serde_json::Value;
+
+fn allowed_values(schema: Value) -> Option<Vec<Value>> {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &schema)
+        .expect("Invalid schema");
+    schema.allowed_values()
+}
+
+#[test]
+fn enum_values() {
+    assert_eq!(allowed_values(json!({"enum": ["red", "green", 3]})),
+        Some(vec![json!("red"), json!("green"), json!(3)]));
+    assert_eq!(allowed_values(json!({"enum": ["red", "green", 3], "type": "string"})),
+        Some(vec![json!("red"), json!("green")]));
+}
+
+#[test]
+fn const_value() {
+    assert_eq!(allowed_values(json!({"const": {"a": 1}})), Some(vec![json!({"a": 1})]));
+    assert_eq!(allowed_values(json!({"const": 1, "minimum": 2})), Some(vec![]));
+    assert_eq!(allowed_values(json!(false)), Some(vec![]));
+}
+
+#[test]
+fn through_ref_and_all_of() {
+    assert_eq!(allowed_values(json!({
+        "definitions": {"size": {"enum": ["S", "M", "L"]}},
+        "$ref": "#/definitions/size"
+    })), Some(vec![json!("S"), json!("M"), json!("L")]));
+    assert_eq!(allowed_values(json!({
+        "allOf": [{"type": "string"}, {"enum": ["S", "M", 10]}]
+    })), Some(vec![json!("S"), json!("M")]));
+}
+
+#[test]
+fn unbounded() {
+    assert_eq!(allowed_values(json!({"type": "string"})), None);
+    assert_eq!(allowed_values(json!(true)), None);
+    assert_eq!(allowed_values(json!({
+        "definitions": {"a": {"$ref": "#/definitions/b"}, "b": {"const": 1}},
+        "$ref": "#/definitions/a"
+    })), None);
+}
@@ -0,0 +1,82 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{Condition, Context, Type, ValidationError};
+
+#[test]
+fn formats_are_reported_by_path() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "format": "record",
+        "properties": {
+            "email": {"type": "string", "format": "email"},
+            "links": {"items": {"format": "uri"}},
+            "a/b": {"$ref": "#/definitions/date"}
+        },
+        "definitions": {
+            "date": {"format": "date"}
+        }
+    })).expect("Invalid schema");
+
+    let annotations = schema.validate_annotated(&json!({
+        "a/b": "2018-01-01",
+        "email": "someone@example.com",
+        "links": ["http://example.com", "http://example.org"]
+    })).expect("Value was invalid");
+    assert_eq!(annotations.formats(), &[
+        ("".to_string(), "record".to_string()),
+        ("/a~1b".to_string(), "date".to_string()),
+        ("/email".to_string(), "email".to_string()),
+        ("/links/0".to_string(), "uri".to_string()),
+        ("/links/1".to_string(), "uri".to_string()),
+    ][..]);
+    assert!(annotations.any_of_matches().is_empty());
+
+    assert_eq!(schema.validate_annotated(&json!({"email": 1})),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))));
+}
+
+#[test]
+fn every_matching_any_of_branch_is_reported() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "anyOf": [
+            {"type": "integer", "format": "int32"},
+            {"type": "string"},
+            {"minimum": 0, "format": "non-negative"}
+        ]
+    })).expect("Invalid schema");
+
+    let branch = |i: usize| base_uri().join(&format!("#/anyOf/{}", i))
+        .expect("Couldn't parse branch URI");
+
+    let annotations = schema.validate_annotated(&json!(5)).expect("Value was invalid");
+    assert_eq!(annotations.any_of_matches(), &[
+        ("".to_string(), branch(0)),
+        ("".to_string(), branch(2)),
+    ][..]);
+    assert_eq!(annotations.formats(), &[
+        ("".to_string(), "int32".to_string()),
+        ("".to_string(), "non-negative".to_string()),
+    ][..]);
+
+    let annotations = schema.validate_annotated(&json!(-5)).expect("Value was invalid");
+    assert_eq!(annotations.any_of_matches(), &[("".to_string(), branch(0))][..]);
+
+    let annotations = schema.validate_annotated(&json!(null)).expect("Value was invalid");
+    assert_eq!(annotations.any_of_matches(), &[("".to_string(), branch(2))][..]);
+    assert_eq!(annotations.formats(), &[("".to_string(), "non-negative".to_string())][..]);
+
+    assert!(schema.validate_annotated(&json!(-0.5)).is_err());
+}
+
+#[test]
+fn format_must_be_a_string() {
+    let mut ctx = Context::default();
+    assert!(ctx.make_schema_unchecked(base_uri(), &json!({"format": 1})).is_err());
+}
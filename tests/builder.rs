@@ -0,0 +1,60 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{Context, SchemaBuilder};
+
+#[test]
+fn builder_produces_expected_json() {
+    let schema = SchemaBuilder::object()
+        .title("Person")
+        .require("name")
+        .property("name", SchemaBuilder::string().min_length(1).max_length(50))
+        .property("age", SchemaBuilder::integer().minimum(0).exclusive_maximum(200))
+        .additional_properties(false)
+        .build();
+
+    assert_eq!(schema, json!({
+        "type": "object",
+        "title": "Person",
+        "required": ["name"],
+        "properties": {
+            "name": {"type": "string", "minLength": 1, "maxLength": 50},
+            "age": {"type": "integer", "minimum": 0, "exclusiveMaximum": 200}
+        },
+        "additionalProperties": false
+    }));
+}
+
+#[test]
+fn and_combines_schemas_with_all_of() {
+    let schema = SchemaBuilder::number()
+        .and(SchemaBuilder::new().minimum(10))
+        .build();
+
+    assert_eq!(schema, json!({
+        "allOf": [{"type": "number"}, {"minimum": 10}]
+    }));
+
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &schema)
+        .expect("Invalid schema");
+    assert!(schema.validate(&json!(12)).is_ok());
+    assert!(schema.validate(&json!(8)).is_err());
+    assert!(schema.validate(&json!("12")).is_err());
+}
+
+#[test]
+fn or_combines_schemas_with_any_of() {
+    let schema = SchemaBuilder::string()
+        .or(SchemaBuilder::integer())
+        .build();
+
+    assert_eq!(schema, json!({
+        "anyOf": [{"type": "string"}, {"type": "integer"}]
+    }));
+}
@@ -0,0 +1,75 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, uri};
+use json_schema::{Context, ValidationError};
+
+#[test]
+fn no_coercion_by_default() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"type": "integer"}))
+        .expect("Invalid schema");
+
+    match schema.validate_coerce(&json!("5")) {
+        Err(ValidationError::ConditionFailed(_)) => {},
+        r => panic!("Wrong result: {:?}", r),
+    }
+    assert_eq!(schema.validate_coerce(&json!(5)), Ok(json!(5)));
+}
+
+#[test]
+fn coerces_nested_strings() {
+    let mut ctx = Context::default();
+    ctx.set_coerce_types(true);
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "properties": {
+            "count": {"type": "integer", "minimum": 1},
+            "price": {"type": "number"},
+            "flags": {"items": {"type": "boolean"}},
+            "name": {"type": "string"}
+        }
+    })).expect("Invalid schema");
+
+    let coerced = schema.validate_coerce(&json!({
+        "count": "5",
+        "price": "2.5",
+        "flags": ["true", false, "false"],
+        "name": "7"
+    }));
+    assert_eq!(coerced, Ok(json!({
+        "count": 5,
+        "price": 2.5,
+        "flags": [true, false, false],
+        "name": "7"
+    })));
+
+    assert!(schema.validate_coerce(&json!({"count": "0"})).is_err());
+    assert!(schema.validate_coerce(&json!({"count": "2.5"})).is_err());
+    assert!(schema.validate_coerce(&json!({"flags": ["yes"]})).is_err());
+}
+
+#[test]
+fn only_validate_coerce_coerces() {
+    let mut ctx = Context::default();
+    ctx.set_coerce_types(true);
+    let schema = ctx.make_schema(base_uri(), &json!({"type": ["null", "integer"]}))
+        .expect("Invalid schema");
+
+    assert_eq!(schema.validate_coerce(&json!("-3")), Ok(json!(-3)));
+    match schema.validate(&json!("-3")) {
+        Err(ValidationError::ConditionFailed(_)) => {},
+        r => panic!("Wrong result: {:?}", r),
+    }
+    assert!(!schema.is_valid(&json!("-3")));
+    assert!(schema.validate(&json!(-3)).is_ok());
+
+    // A coercible value still fails `type`, so `not` accepts it.
+    let not = ctx.make_schema(uri("http://example.com/not.json"), &json!({"not": {"type": "integer"}}))
+        .expect("Invalid schema");
+    assert!(not.validate(&json!("5")).is_ok());
+    assert!(not.is_valid(&json!("5")));
+}
@@ -0,0 +1,276 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, make_schema, uri};
+use json_schema::{BoundKind, Condition, Context, Diagnostic, FromValueError, Type, ValidationError};
+use serde_json::Number;
+
+#[test]
+fn all_of_with_false_branch_always_fails() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"allOf": [true, false]}));
+
+    for value in &[json!(null), json!(1), json!("x"), json!({})] {
+        assert_eq!(schema.validate(value), Err(ValidationError::AllOfBranchFailed {
+            index: 1,
+            cause: Box::new(ValidationError::NoValuesPass(value.clone())),
+        }));
+    }
+}
+
+#[test]
+fn all_of_reports_failing_branch_index() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"allOf": [{"type": "integer"}, {"minimum": 10}]}));
+
+    assert!(schema.validate(&json!(10)).is_ok());
+    assert_eq!(schema.validate(&json!("x")), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))),
+    }));
+    assert_eq!(schema.validate(&json!(5)), Err(ValidationError::AllOfBranchFailed {
+        index: 1,
+        cause: Box::new(ValidationError::NumberOutOfRange {
+            bound: 10.into(),
+            actual: 5.into(),
+            kind: BoundKind::Minimum,
+        }),
+    }));
+}
+
+#[test]
+fn all_of_ignores_branches_after_false() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"allOf": [{"type": "integer"}, {"minimum": 10}, false, {"$ref": "missing.json"}]}));
+
+    // The branches before the `false` one are still checked first.
+    for &(ref value, index) in &[(json!("x"), 0), (json!(5), 1), (json!(10), 2)] {
+        match schema.validate(value) {
+            Err(ValidationError::AllOfBranchFailed { index: i, .. }) => assert_eq!(i, index),
+            r => panic!("Wrong result: {:?}", r),
+        }
+        assert!(!schema.is_valid(value));
+    }
+    assert_eq!(schema.validate(&json!(10)), Err(ValidationError::AllOfBranchFailed {
+        index: 2,
+        cause: Box::new(ValidationError::NoValuesPass(json!(10))),
+    }));
+
+    // So a reference that can't be resolved still takes priority.
+    let schema = make_schema(&mut ctx, json!({"not": {"allOf": [{"$ref": "missing.json"}, false]}}));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::BadReference(uri("http://example.com/missing.json"))),
+    }));
+
+    let schema = make_schema(&mut ctx, json!({"allOf": [{"type": "integer"}, {"minimum": 10}, true]}));
+    assert!(schema.validate(&json!(10)).is_ok());
+    assert!(schema.is_valid(&json!(10)));
+
+    // So does a reference to the false schema, here or in another document.
+    let schema = make_schema(&mut ctx, json!({
+        "allOf": [{"$ref": "#/definitions/no"}, {"$ref": "missing.json"}],
+        "definitions": {"no": false}
+    }));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::NoValuesPass(json!(1))),
+    }));
+    assert!(!schema.is_valid(&json!(1)));
+    ctx.make_schema(uri("http://example.com/no.json"), &json!(false)).expect("Invalid schema");
+    let schema = make_schema(&mut ctx, json!({"allOf": [{"$ref": "no.json"}, {"$ref": "missing.json"}]}));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::NoValuesPass(json!(1))),
+    }));
+    assert!(!schema.is_valid(&json!(1)));
+}
+
+#[test]
+fn all_of_keeps_branches_after_false() {
+    let mut ctx = Context::default();
+    let json = json!({"allOf": [{"type": "string"}, false, {"minimum": 3}]});
+    let schema = make_schema(&mut ctx, json.clone());
+    assert_eq!(schema.to_value(), json);
+
+    // Only validate stops at the false branch; the full reports don't.
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))),
+    }));
+    assert_eq!(schema.validate_tree(&json!(1)).to_flat_vec().len(), 3);
+    let mut indices = Vec::new();
+    schema.validate_each(&json!(1), |err| {
+        if let ValidationError::AllOfBranchFailed { index, .. } = err {
+            indices.push(index);
+        }
+        true
+    });
+    assert_eq!(indices, vec![0, 1, 2]);
+}
+
+#[test]
+fn any_of_with_true_branch_always_passes() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"anyOf": [false, true]}));
+
+    for value in &[json!(null), json!(1), json!("x"), json!({})] {
+        assert!(schema.validate(value).is_ok());
+    }
+}
+
+#[test]
+fn not_anything_rejects_everything() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": {}}));
+
+    assert!(schema.validate(&json!(1)).is_err());
+    assert!(schema.validate(&json!("x")).is_err());
+    assert!(schema.validate(&json!(null)).is_err());
+}
+
+#[test]
+fn not_inverts_subschema() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": {"type": "string"}}));
+
+    assert!(schema.validate(&json!("x")).is_err());
+    assert!(schema.validate(&json!(1)).is_ok());
+}
+
+#[test]
+fn not_false_accepts_everything() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": false}));
+
+    for value in &[json!(null), json!(1), json!("x"), json!({})] {
+        assert!(schema.validate(value).is_ok());
+    }
+}
+
+#[test]
+fn not_propagates_bad_references() {
+    let missing = uri("http://example.com/missing.json");
+
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": {"$ref": "missing.json"}}));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::BadReference(missing.clone())));
+
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": {"allOf": [{"$ref": "missing.json"}]}}));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::BadReference(missing.clone())),
+    }));
+
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": {"$ref": "#/missing"}}));
+    let pointer = uri("http://example.com/schema.json#/missing");
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::BadReference(pointer)));
+
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"not": {"anyOf": [{"type": "string"}, {"$ref": "missing.json"}]}}));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AnyOfAllFailed(vec![
+        ValidationError::ConditionFailed(Condition::Type(vec![Type::String])),
+        ValidationError::BadReference(missing),
+    ])));
+}
+
+#[test]
+fn any_of_reports_every_branch_error() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"anyOf": [{"type": "string"}, {"minimum": 10}]}));
+
+    assert!(schema.validate(&json!("x")).is_ok());
+    assert_eq!(schema.validate(&json!(5)), Err(ValidationError::AnyOfAllFailed(vec![
+        ValidationError::ConditionFailed(Condition::Type(vec![Type::String])),
+        ValidationError::NumberOutOfRange { bound: 10.into(), actual: 5.into(), kind: BoundKind::Minimum },
+    ])));
+}
+
+#[test]
+fn one_of_reports_no_or_many_matches() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"oneOf": [{"type": "integer"}, {"minimum": 10}, false]}));
+
+    assert!(schema.validate(&json!(5)).is_ok());
+    assert!(schema.validate(&json!(10.5)).is_ok());
+    assert_eq!(schema.validate(&json!(10)), Err(ValidationError::OneOfManyPassed(vec![0, 1])));
+    assert_eq!(schema.validate(&json!(5.5)), Err(ValidationError::OneOfAllFailed(vec![
+        ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer])),
+        ValidationError::NumberOutOfRange {
+            bound: 10.into(),
+            actual: Number::from_f64(5.5).unwrap(),
+            kind: BoundKind::Minimum,
+        },
+        ValidationError::NoValuesPass(json!(5.5)),
+    ])));
+}
+
+#[test]
+fn one_of_discriminator_selects_branch() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "definitions": {
+            "cat": {"required": ["kind", "lives"], "properties": {"lives": {"type": "integer"}}},
+            "dog": {"required": ["kind"], "properties": {"good": {"type": "boolean"}}}
+        },
+        "oneOf": [{"$ref": "#/definitions/cat"}, {"$ref": "#/definitions/dog"}],
+        "discriminator": {
+            "propertyName": "kind",
+            "mapping": {"cat": "#/definitions/cat", "dog": "#/definitions/dog"}
+        }
+    }));
+
+    // Without the discriminator, this would match both branches.
+    assert!(schema.validate(&json!({"kind": "dog", "lives": 9})).is_ok());
+    assert!(schema.is_valid(&json!({"kind": "dog", "lives": 9})));
+    assert_eq!(schema.validate(&json!({"kind": "cat", "lives": "nine"})),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))));
+    assert!(!schema.is_valid(&json!({"kind": "cat", "lives": "nine"})));
+
+    // An unmapped or missing value falls back to checking every branch.
+    assert!(schema.validate(&json!({"kind": "bird", "good": true})).is_ok());
+    assert_eq!(schema.validate(&json!({"kind": "bird", "lives": 9})),
+        Err(ValidationError::OneOfManyPassed(vec![0, 1])));
+    assert!(schema.validate(&json!({"lives": 9})).is_err());
+
+    assert_eq!(schema.to_value()["discriminator"], json!({
+        "propertyName": "kind",
+        "mapping": {
+            "cat": "http://example.com/schema.json#/definitions/cat",
+            "dog": "http://example.com/schema.json#/definitions/dog"
+        }
+    }));
+    assert!(ctx.validate_references().is_ok());
+}
+
+#[test]
+fn one_of_discriminator_must_name_a_property() {
+    let mut ctx = Context::default();
+    for discriminator in &[json!({}), json!({"propertyName": 1}), json!({"propertyName": "kind", "mapping": {"a": 1}})] {
+        match ctx.make_schema_unchecked(base_uri(), &json!({"oneOf": [true], "discriminator": discriminator})) {
+            Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "discriminator"),
+            r => panic!("Wrong result: {:?}", r),
+        }
+    }
+}
+
+#[test]
+fn discriminator_without_one_of_is_unknown() {
+    let schema = json!({"discriminator": {"propertyName": "kind"}});
+
+    let mut ctx = Context::default();
+    let lenient = ctx.make_schema(uri("http://example.com/lenient.json"), &schema).expect("Invalid schema");
+    assert_eq!(lenient.diagnostics(), &[Diagnostic::UnknownKeyword("discriminator".to_string())][..]);
+
+    ctx.set_strict_keywords(true);
+    match ctx.make_schema(uri("http://example.com/strict.json"), &schema) {
+        Err(FromValueError::UnknownKeyword(_, ref keyword)) => assert_eq!(keyword, "discriminator"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
@@ -0,0 +1,30 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use json_schema::{Context, JsonSchema};
+use serde_json::Value;
+use url::Url;
+
+/// Parses a URI, panicking if it's invalid.
+pub fn uri(s: &str) -> Url {
+    Url::parse(s).expect("Couldn't parse URI")
+}
+
+/// The URI that tests create their schemas at.
+pub fn base_uri() -> Url {
+    uri("http://example.com/schema.json")
+}
+
+/// Creates a schema at `base_uri()` in the given Context.
+pub fn make_schema<'a>(ctx: &'a mut Context, json: Value) -> JsonSchema<'a> {
+    ctx.make_schema(base_uri(), &json)
+        .expect("Invalid schema")
+}
+
+/// Returns whether the data is valid against the schema, which is created in
+/// a new Context.
+pub fn is_valid(schema: Value, data: Value) -> bool {
+    let mut ctx = Context::default();
+    make_schema(&mut ctx, schema).validate(&data).is_ok()
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "contains-count")]
+
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{Condition, Context, FromValueError, ValidationError};
+
+#[test]
+fn min_contains_counts_matching_elements() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "contains": {"type": "string"},
+        "minContains": 2
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!(["x", 1, "y"])).is_ok());
+    assert!(schema.validate(&json!(["x", "y", "z"])).is_ok());
+    assert!(schema.is_valid(&json!(["x", 1, "y"])));
+
+    match schema.validate(&json!(["x", 1, 2])) {
+        Err(ValidationError::ConditionFailed(Condition::Contains(_, Some(2), None))) => {},
+        r => panic!("Wrong result: {:?}", r),
+    }
+    assert!(!schema.is_valid(&json!(["x", 1, 2])));
+    match schema.validate(&json!([1, 2])) {
+        Err(ValidationError::ContainsNoneMatched(ref errors)) => assert_eq!(errors.len(), 2),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn max_contains_bounds_matching_elements() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "contains": {"type": "string"},
+        "maxContains": 2
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!(["x", 1, "y"])).is_ok());
+    assert!(schema.validate(&json!(["x", "y", "z"])).is_err());
+    assert!(!schema.is_valid(&json!(["x", "y", "z"])));
+    assert!(schema.validate(&json!([1])).is_err());
+}
+
+#[test]
+fn min_contains_zero_allows_no_matches() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "contains": {"type": "string"},
+        "minContains": 0,
+        "maxContains": 1
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!([])).is_ok());
+    assert!(schema.validate(&json!([1, 2])).is_ok());
+    assert!(schema.validate(&json!([1, "x"])).is_ok());
+    assert!(schema.validate(&json!(["x", "y"])).is_err());
+}
+
+#[test]
+fn contains_counts_round_trip_and_are_checked() {
+    let mut ctx = Context::default();
+    let json = json!({"contains": {"type": "string"}, "minContains": 2, "maxContains": 3});
+    let schema = ctx.make_schema(base_uri(), &json).expect("Invalid schema");
+    assert_eq!(schema.to_value(), json);
+
+    for value in &[json!(-1), json!(1.5)] {
+        match ctx.make_schema_unchecked(base_uri(), &json!({"contains": {}, "minContains": value})) {
+            Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "minContains"),
+            r => panic!("Wrong result: {:?}", r),
+        }
+    }
+    match ctx.make_schema_unchecked(base_uri(), &json!({"maxContains": "3"})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "maxContains"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
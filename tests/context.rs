@@ -0,0 +1,685 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::uri;
+use json_schema::{Condition, Context, Diagnostic, FromValueError, Lint, SchemaId, Type, ValidationError};
+use serde_json::Value;
+
+#[test]
+fn new_context_has_metaschema_only_with_feature() {
+    let ctx = Context::new();
+    let metaschema = uri("http://json-schema.org/draft-06/schema#");
+    assert_eq!(ctx.get(&metaschema).is_some(), cfg!(feature = "metaschema"));
+    assert_eq!(ctx.is_empty(), !cfg!(feature = "metaschema"));
+
+    let mut ctx = Context::new();
+    ctx.make_schema(uri("http://example.com/schema.json"), &json!({"type": "integer"}))
+        .expect("Invalid schema");
+    assert!(ctx.validate(&uri("http://example.com/schema.json"), &json!(1)).is_ok());
+}
+
+#[test]
+fn validate_by_declared_id() {
+    let mut ctx = Context::default();
+    ctx.make_schema(SchemaId::anonymous(), &json!({
+        "$id": "https://example.com/person",
+        "type": "object",
+        "required": ["name"],
+        "properties": {"name": {"type": "string"}}
+    })).expect("Invalid schema");
+    ctx.make_schema(uri("https://example.com/team"), &json!({
+        "type": "array",
+        "items": {"$ref": "person"}
+    })).expect("Invalid schema");
+
+    let person = uri("https://example.com/person");
+    assert!(ctx.validate(&person, &json!({"name": "Alice"})).is_ok());
+    assert!(ctx.validate(&person, &json!({"name": 1})).is_err());
+    assert!(ctx.validate(&person, &json!({})).is_err());
+
+    let team = uri("https://example.com/team");
+    assert!(ctx.validate(&team, &json!([{"name": "Alice"}, {"name": "Bob"}])).is_ok());
+    assert!(ctx.validate(&team, &json!([{"name": "Alice"}, {}])).is_err());
+    assert_eq!(ctx.validate_references(), Ok(()));
+}
+
+#[test]
+fn best_match_picks_schema_with_fewest_errors() {
+    let mut ctx = Context::default();
+    let candidates = vec![
+        uri("http://example.com/a.json"),
+        uri("http://example.com/b.json"),
+        uri("http://example.com/c.json"),
+    ];
+    ctx.make_schema(candidates[0].clone(), &json!({
+        "type": "string",
+        "required": ["title"]
+    })).expect("Invalid schema");
+    ctx.make_schema(candidates[1].clone(), &json!({
+        "type": "object",
+        "required": ["name"]
+    })).expect("Invalid schema");
+    ctx.make_schema(candidates[2].clone(), &json!({
+        "type": "object",
+        "required": ["name", "id"],
+        "minimum": 0
+    })).expect("Invalid schema");
+
+    let value = json!({"id": 1});
+    assert_eq!(ctx.best_match(&candidates, &value), Some((&candidates[1], 1)));
+    assert_eq!(ctx.best_match(&candidates[2..], &value), Some((&candidates[2], 1)));
+    assert_eq!(ctx.best_match(&[uri("http://example.com/missing.json")], &value), None);
+}
+
+#[test]
+fn classify_picks_first_matching_schema() {
+    let mut ctx = Context::default();
+    let candidates = vec![
+        uri("http://example.com/a.json"),
+        uri("http://example.com/b.json"),
+        uri("http://example.com/c.json"),
+    ];
+    ctx.make_schema(candidates[0].clone(), &json!({"type": "string"}))
+        .expect("Invalid schema");
+    ctx.make_schema(candidates[1].clone(), &json!({"type": "object", "required": ["name"]}))
+        .expect("Invalid schema");
+    ctx.make_schema(candidates[2].clone(), &json!({"type": "object", "required": ["id"]}))
+        .expect("Invalid schema");
+
+    let value = json!({"name": "x"});
+    assert_eq!(ctx.classify(&candidates, &value), Some(candidates[1].clone()));
+    assert_eq!(ctx.classify(&candidates[2..], &value), None);
+    assert_eq!(ctx.classify(&[uri("http://example.com/missing.json")], &value), None);
+}
+
+#[test]
+fn strict_keywords_rejects_unknown_keywords() {
+    let schema = json!({"maxiumm": 3});
+
+    let mut ctx = Context::default();
+    assert!(ctx.make_schema(uri("http://example.com/lenient.json"), &schema).is_ok());
+
+    let mut ctx = Context::default();
+    ctx.set_strict_keywords(true);
+    match ctx.make_schema(uri("http://example.com/strict.json"), &schema) {
+        Err(FromValueError::UnknownKeyword(_, ref keyword)) => assert_eq!(keyword, "maxiumm"),
+        Err(err) => panic!("Wrong error: {:?}", err),
+        Ok(_) => panic!("Unknown keyword was accepted"),
+    }
+}
+
+#[test]
+fn diagnostics_report_unenforced_keywords() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/a.json"), &json!({"format": "email"}))
+        .expect("Invalid schema");
+    assert_eq!(schema.diagnostics(), &[Diagnostic::FormatNotEnforced("email".to_string())][..]);
+
+    let schema = ctx.make_schema(uri("http://example.com/b.json"), &json!({
+        "maxiumm": 3,
+        "properties": {"a": {"format": "date"}}
+    })).expect("Invalid schema");
+    assert_eq!(schema.diagnostics(), &[Diagnostic::UnknownKeyword("maxiumm".to_string())][..]);
+    let a = ctx.get(&uri("http://example.com/b.json#/properties/a")).expect("Subschema wasn't added");
+    assert_eq!(a.diagnostics(), &[Diagnostic::FormatNotEnforced("date".to_string())][..]);
+
+    let schema = ctx.make_schema(uri("http://example.com/c.json"), &json!({"type": "string"}))
+        .expect("Invalid schema");
+    assert!(schema.diagnostics().is_empty());
+}
+
+#[test]
+fn validate_by_uri() {
+    let mut ctx = Context::default();
+    let schema_uri = uri("http://example.com/positive.json");
+    ctx.make_schema(schema_uri.clone(), &json!({
+        "type": "integer",
+        "minimum": 1
+    })).expect("Invalid schema");
+
+    assert!(ctx.validate(&schema_uri, &json!(1)).is_ok());
+    assert!(ctx.validate(&schema_uri, &json!(100)).is_ok());
+    assert!(ctx.validate(&schema_uri, &json!(0)).is_err());
+    assert!(ctx.validate(&schema_uri, &json!("1")).is_err());
+
+    let missing = uri("http://example.com/missing.json");
+    assert_eq!(ctx.validate(&missing, &json!(1)), Err(ValidationError::BadReference(missing.clone())));
+}
+
+#[test]
+fn try_get_names_the_missing_uri() {
+    let mut ctx = Context::default();
+    let schema_uri = uri("http://example.com/a.json");
+    ctx.make_schema(schema_uri.clone(), &json!({"definitions": {"x": {"type": "string"}}}))
+        .expect("Invalid schema");
+
+    let x = uri("http://example.com/a.json#/definitions/x");
+    assert_eq!(ctx.try_get(&x).map(|schema| schema.id().clone()), Ok(x));
+
+    let missing = uri("http://example.com/a.json#/definitions/y");
+    match ctx.try_get(&missing) {
+        Err(ValidationError::BadReference(ref uri)) => assert_eq!(uri, &missing),
+        Err(err) => panic!("Wrong error: {:?}", err),
+        Ok(schema) => panic!("Found a schema at {}", schema.id()),
+    }
+}
+
+#[test]
+fn validate_references_reports_dangling_refs() {
+    let mut ctx = Context::default();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "properties": {
+            "b": {"$ref": "b.json"},
+            "c": {"$ref": "c.json#/definitions/c"}
+        },
+        "allOf": [{"$ref": "b.json"}]
+    })).expect("Invalid schema");
+
+    assert_eq!(ctx.validate_references(), Err(vec![
+        uri("http://example.com/b.json"),
+        uri("http://example.com/c.json#/definitions/c"),
+    ]));
+
+    ctx.make_schema(uri("http://example.com/b.json"), &json!({}))
+        .expect("Invalid schema");
+    ctx.make_schema(uri("http://example.com/c.json#/definitions/c"), &json!({}))
+        .expect("Invalid schema");
+    assert_eq!(ctx.validate_references(), Ok(()));
+}
+
+#[test]
+fn evaluation_order_checks_type_before_properties() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "properties": {"a": {"type": "integer"}},
+        "type": "object"
+    })).expect("Invalid schema");
+
+    assert_eq!(schema.evaluation_order(), vec!["type", "properties"]);
+}
+
+#[test]
+fn evaluation_order_puts_selective_checks_first() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "allOf": [{"minProperties": 1}],
+        "properties": {"a": {"type": "integer"}},
+        "required": ["a"],
+        "enum": [{"a": 1}, {"a": 2}],
+        "type": "object"
+    })).expect("Invalid schema");
+
+    let order = schema.evaluation_order();
+    let position = |keyword| order.iter().position(|&k| k == keyword)
+        .expect("Keyword wasn't checked");
+    assert_eq!(order.len(), 5);
+    assert_eq!(position("type"), 0);
+    for &cheap in &["enum", "required"] {
+        assert!(position(cheap) < position("properties"));
+    }
+    assert_eq!(position("allOf"), 4);
+}
+
+#[test]
+fn evaluation_order_checks_counts_before_items() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "definitions": {
+            "big": {
+                "allOf": [{"type": "string"}, {"minLength": 1}],
+                "pattern": "^[a-z]+$"
+            }
+        },
+        "type": "array",
+        "items": {"$ref": "#/definitions/big"},
+        "maxItems": 0
+    })).expect("Invalid schema");
+
+    assert_eq!(schema.evaluation_order(), vec!["type", "maxItems", "items"]);
+    assert_eq!(schema.validate(&json!([1])),
+        Err(ValidationError::ConditionFailed(Condition::MaxItems(0))));
+}
+
+#[test]
+fn bundle_round_trips() {
+    let mut ctx = Context::default();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "title": "A",
+        "description": "Everything at once.",
+        "definitions": {
+            "positive": {"$id": "#positive", "type": "integer", "exclusiveMinimum": 0},
+            "other": {"$id": "http://example.com/other.json", "multipleOf": 0.5}
+        },
+        "type": ["object", "null"],
+        "properties": {
+            "a": {"$ref": "#positive"},
+            "b": {"items": [{"enum": [1, "x"]}, true], "additionalItems": false},
+            "c": {"items": {"const": null}, "maxItems": 3, "uniqueItems": true}
+        },
+        "patternProperties": {"^x-": {"pattern": "^[a-z]+$", "maxLength": 8}},
+        "additionalProperties": {"$ref": "http://example.com/other.json"},
+        "dependencies": {"a": ["b"], "b": {"minProperties": 2}},
+        "propertyNames": {"minLength": 1},
+        "required": ["a"],
+        "allOf": [{"maximum": 10}],
+        "anyOf": [{"not": {"contains": {"minimum": 5}}}, {}],
+        "oneOf": [{"maxProperties": 5}, false]
+    })).expect("Invalid schema");
+    ctx.make_schema(uri("http://example.com/b.json"), &json!(false))
+        .expect("Invalid schema");
+
+    let bundle = ctx.to_bundle();
+    assert!(bundle.get("http://example.com/a.json#/properties/b").is_some());
+    assert_eq!(bundle["http://example.com/b.json"], json!(false));
+
+    let mut loaded = Context::default();
+    loaded.load_bundle(&bundle).expect("Invalid bundle");
+    assert_eq!(loaded, ctx);
+    assert_eq!(loaded.to_bundle(), bundle);
+}
+
+#[test]
+fn to_value_full_keeps_metadata() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/person.json"), &json!({
+        "$schema": "http://json-schema.org/draft-06/schema#",
+        "title": "Person",
+        "description": "Someone.",
+        "default": {"name": "Nobody"},
+        "examples": [{"name": "Alice", "age": 30}],
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+            "name": {"type": "string", "minLength": 1, "default": ""},
+            "age": {"description": "In years.", "type": "integer", "minimum": 0, "examples": [30]}
+        },
+        "format": "person",
+        "x-unknown": true
+    })).expect("Invalid schema");
+
+    assert_eq!(schema.to_value_full(), json!({
+        "$schema": "http://json-schema.org/draft-06/schema#",
+        "$id": "http://example.com/person.json",
+        "title": "Person",
+        "description": "Someone.",
+        "default": {"name": "Nobody"},
+        "examples": [{"name": "Alice", "age": 30}],
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+            "name": {"type": "string", "minLength": 1, "default": ""},
+            "age": {"description": "In years.", "type": "integer", "minimum": 0, "examples": [30]}
+        },
+        "format": "person"
+    }));
+}
+
+#[test]
+fn examples_are_kept() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "type": "integer",
+        "examples": [1, 2]
+    })).expect("Invalid schema");
+    assert_eq!(schema.examples(), &[json!(1), json!(2)]);
+    assert!(schema.validate(&json!("not an example")).is_err());
+
+    let schema = ctx.make_schema(uri("http://example.com/b.json"), &json!({}))
+        .expect("Invalid schema");
+    assert!(schema.examples().is_empty());
+
+    match ctx.make_schema_unchecked(uri("http://example.com/c.json"), &json!({"examples": 1})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "examples"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn defaults_are_kept() {
+    let mut ctx = Context::default();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "properties": {
+            "name": {"type": "string", "default": "Anonymous"},
+            "tags": {"default": []}
+        }
+    })).expect("Invalid schema");
+
+    let name = ctx.get(&uri("http://example.com/a.json#/properties/name"))
+        .expect("Missing schema");
+    assert_eq!(name.default_value(), Some(&json!("Anonymous")));
+    let tags = ctx.get(&uri("http://example.com/a.json#/properties/tags"))
+        .expect("Missing schema");
+    assert_eq!(tags.default_value(), Some(&json!([])));
+    let root = ctx.get(&uri("http://example.com/a.json")).expect("Missing schema");
+    assert_eq!(root.default_value(), None);
+}
+
+#[test]
+fn lint_reports_defaults_and_examples_that_fail() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri("http://example.com/a.json"), &json!({"type": "integer", "default": "oops"}))
+        .expect("Invalid schema");
+    let type_failed = ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]));
+    assert_eq!(schema.lint(), vec![Lint::DefaultViolatesSchema(uri("http://example.com/a.json"), type_failed.clone())]);
+
+    let schema = ctx.make_schema(uri("http://example.com/b.json"), &json!({
+        "definitions": {"count": {"type": "integer", "examples": [1, "two", 3]}},
+        "default": {},
+        "properties": {
+            "count": {"$ref": "#/definitions/count"},
+            "name": {"type": "string", "default": "x"}
+        }
+    })).expect("Invalid schema");
+    assert_eq!(schema.lint(), vec![
+        Lint::ExampleViolatesSchema(uri("http://example.com/b.json#/definitions/count"), 1, type_failed),
+    ]);
+}
+
+#[test]
+fn load_bundle_rejects_bad_uris() {
+    let mut ctx = Context::default();
+    match ctx.load_bundle(&json!({"not a uri": {}})) {
+        Err(FromValueError::InvalidId(_, ref id, _)) => assert_eq!(id, "not a uri"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn compose_all_of_combines_registered_schemas() {
+    let mut ctx = Context::default();
+    let base = uri("http://example.com/base.json");
+    let extra = uri("http://example.com/extra.json");
+    ctx.make_schema(base.clone(), &json!({
+        "type": "object",
+        "properties": {"name": {"type": "string"}}
+    })).expect("Invalid schema");
+    ctx.make_schema(extra.clone(), &json!({"required": ["name"]}))
+        .expect("Invalid schema");
+
+    let composed = ctx.compose_all_of(&[base.clone(), extra])
+        .expect("Couldn't compose schemas");
+    assert!(ctx.validate(&composed, &json!({"name": "x"})).is_ok());
+    assert!(ctx.validate(&composed, &json!({})).is_err());
+    assert!(ctx.validate(&composed, &json!({"name": 1})).is_err());
+    assert!(ctx.validate(&composed, &json!("x")).is_err());
+
+    let other = ctx.compose_all_of(&[base]).expect("Couldn't compose schemas");
+    assert!(other != composed);
+    assert!(ctx.validate(&other, &json!({})).is_ok());
+
+    match ctx.compose_all_of(&[]) {
+        Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "allOf"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn validate_transient_leaves_context_unchanged() {
+    let mut ctx = Context::default();
+    ctx.make_schema(uri("http://example.com/positive.json"), &json!({
+        "type": "integer",
+        "minimum": 1,
+        "definitions": {"small": {"$id": "#small", "maximum": 5}}
+    })).expect("Invalid schema");
+    let before = ctx.clone();
+
+    let schema = json!({"items": {"$ref": "http://example.com/positive.json"}, "maxItems": 2});
+    assert!(ctx.validate_transient(&schema, &json!([1, 2])).is_ok());
+    assert!(ctx.validate_transient(&schema, &json!([1, 2, 3])).is_err());
+    assert!(ctx.validate_transient(&schema, &json!([0])).is_err());
+    let schema = json!({"$ref": "http://example.com/positive.json#small"});
+    assert!(ctx.validate_transient(&schema, &json!(5)).is_ok());
+    assert!(ctx.validate_transient(&schema, &json!(6)).is_err());
+    assert_eq!(ctx, before);
+
+    match ctx.validate_transient(&json!({"pattern": "("}), &json!("a")) {
+        Err(ValidationError::InvalidSchema(ref err)) => match **err {
+            FromValueError::BadPattern(..) => {},
+            ref err => panic!("Wrong error: {:?}", err),
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn estimated_size_grows_with_schemas() {
+    let mut ctx = Context::default();
+    let empty_len = ctx.len();
+    let empty_size = ctx.estimated_size();
+
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({"type": "string"}))
+        .expect("Invalid schema");
+    assert_eq!(ctx.len(), empty_len + 1);
+    let one_size = ctx.estimated_size();
+    assert!(one_size > empty_size);
+
+    ctx.make_schema(uri("http://example.com/b.json"), &json!({
+        "title": "B",
+        "properties": {"name": {"pattern": "^[a-z]+$"}},
+        "enum": [{"name": "alice"}, {"name": "bob"}]
+    })).expect("Invalid schema");
+    assert_eq!(ctx.len(), empty_len + 3);
+    assert!(ctx.estimated_size() > one_size);
+}
+
+#[test]
+fn schema_ids() {
+    let a = SchemaId::anonymous();
+    let b = SchemaId::anonymous();
+    assert_ne!(a, b);
+    assert!(a.to_string().starts_with("urn:json-schema:anonymous:"));
+
+    let id = "http://example.com/a.json".parse::<SchemaId>().expect("Couldn't parse ID");
+    assert_eq!(id.to_string(), "http://example.com/a.json");
+    assert_eq!(id.as_url(), &uri("http://example.com/a.json"));
+    assert!("not a uri".parse::<SchemaId>().is_err());
+
+    let mut ctx = Context::default();
+    ctx.make_schema(id.clone(), &json!({"type": "integer"})).expect("Invalid schema");
+    ctx.make_schema(a.clone(), &json!({"$ref": "http://example.com/a.json"})).expect("Invalid schema");
+    assert!(ctx.validate(&a, &json!(1)).is_ok());
+    assert!(ctx.get(&a).expect("Missing schema").validate(&json!("x")).is_err());
+    assert!(ctx.get(&id).is_some());
+}
+
+#[test]
+fn replace_definition_updates_dependents() {
+    let mut ctx = Context::default();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "definitions": {
+            "x": {"type": "integer", "properties": {"n": {"minimum": 0}}},
+            "y": {"type": "boolean"}
+        },
+        "$ref": "#/definitions/y"
+    })).expect("Invalid schema");
+    ctx.make_schema(uri("http://example.com/b.json"), &json!({"$ref": "a.json#/definitions/x"}))
+        .expect("Invalid schema");
+    let b = uri("http://example.com/b.json");
+    assert!(ctx.validate(&b, &json!(1)).is_ok());
+    assert!(ctx.validate(&b, &json!("s")).is_err());
+
+    let x = uri("http://example.com/a.json#/definitions/x");
+    ctx.replace(x.clone(), &json!({"type": "string"})).expect("Invalid schema");
+    assert!(ctx.validate(&b, &json!("s")).is_ok());
+    assert!(ctx.validate(&b, &json!(1)).is_err());
+    assert!(ctx.get(&uri("http://example.com/a.json#/definitions/x/properties/n")).is_none());
+    assert!(ctx.validate(&uri("http://example.com/a.json"), &json!(true)).is_ok());
+
+    let before = ctx.clone();
+    assert!(ctx.replace(x, &json!({"type": 5})).is_err());
+    assert_eq!(ctx, before);
+}
+
+#[test]
+fn anonymous_schemas_get_distinct_uris() {
+    let mut ctx = Context::default();
+    let a = ctx.make_anonymous_schema(&json!({"type": "integer"})).expect("Invalid schema");
+    let b = ctx.make_anonymous_schema(&json!({
+        "definitions": {"name": {"type": "string"}},
+        "properties": {"name": {"$ref": "#/definitions/name"}}
+    })).expect("Invalid schema");
+    assert_ne!(a, b);
+
+    assert!(ctx.validate(&a, &json!(1)).is_ok());
+    assert!(ctx.validate(&a, &json!("x")).is_err());
+    assert!(ctx.validate(&b, &json!({"name": "x"})).is_ok());
+    assert!(ctx.validate(&b, &json!({"name": 1})).is_err());
+}
+
+#[test]
+fn clones_share_schemas_until_changed() {
+    let mut ctx = Context::new();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({"type": "integer"}))
+        .expect("Invalid schema");
+
+    let mut clone = ctx.clone();
+    assert_eq!(clone, ctx);
+    clone.replace(uri("http://example.com/a.json"), &json!({"type": "string"}))
+        .expect("Invalid schema");
+    clone.make_schema(uri("http://example.com/b.json"), &json!({"minimum": 0}))
+        .expect("Invalid schema");
+
+    assert!(ctx.validate(&uri("http://example.com/a.json"), &json!(1)).is_ok());
+    assert!(ctx.get(&uri("http://example.com/b.json")).is_none());
+    assert!(clone.validate(&uri("http://example.com/a.json"), &json!("x")).is_ok());
+
+    let shared = ctx.clone();
+    let handle = ::std::thread::spawn(move || {
+        shared.validate(&uri("http://example.com/a.json"), &json!(1)).is_ok()
+    });
+    assert!(handle.join().expect("Validating thread panicked"));
+}
+
+#[test]
+fn validate_each_streams_errors() {
+    let mut ctx = Context::new();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "$ref": "#/definitions/checks",
+        "definitions": {
+            "checks": {"type": "string", "maxLength": 3, "pattern": "^a"}
+        }
+    })).expect("Invalid schema");
+    let schema = ctx.get(&uri("http://example.com/a.json")).expect("Schema wasn't added");
+
+    let mut errors = Vec::new();
+    assert!(schema.validate_each(&json!("bcde"), |err| {
+        errors.push(err);
+        true
+    }));
+    assert_eq!(Err(errors), schema.validate_all(&json!("bcde")));
+
+    let mut count = 0;
+    assert!(schema.validate_each(&json!("abc"), |_| {
+        count += 1;
+        true
+    }));
+    assert_eq!(count, 0);
+
+    // Stopping at the first error skips the rest.
+    let mut count = 0;
+    assert!(!schema.validate_each(&json!("bcde"), |_| {
+        count += 1;
+        false
+    }));
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn validate_each_streams_subschema_errors() {
+    let mut ctx = Context::new();
+    ctx.make_schema(uri("http://example.com/a.json"), &json!({
+        "allOf": [{"maxLength": 3, "pattern": "^a"}],
+        "items": {"minimum": 2, "multipleOf": 2},
+        "additionalProperties": {"type": "integer", "minimum": 2}
+    })).expect("Invalid schema");
+    let schema = ctx.get(&uri("http://example.com/a.json")).expect("Schema wasn't added");
+
+    let each = |json: &Value| {
+        let mut errors = Vec::new();
+        schema.validate_each(json, |err| {
+            errors.push(err);
+            true
+        });
+        errors
+    };
+    let branch = |cause| ValidationError::AllOfBranchFailed { index: 0, cause: Box::new(cause) };
+    let errors = each(&json!("bcde"));
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0], branch(ValidationError::ConditionFailed(Condition::MaxLength(3))));
+    assert_eq!(each(&json!([4, 1])).len(), 2);
+    let additional = |cause| ValidationError::AdditionalPropertyFailed { key: "x".to_string(), cause: Box::new(cause) };
+    let errors = each(&json!({"x": 1.5}));
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0], additional(ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))));
+
+    // validate_all still reports one error per failing condition.
+    assert_eq!(schema.validate_all(&json!([4, 1])).map_err(|errs| errs.len()), Err(1));
+}
+
+#[test]
+fn freeze_requires_every_reference_to_resolve() {
+    let bundle = json!({
+        "http://example.com/a.json": {"properties": {"b": {"$ref": "b.json"}}},
+        "http://example.com/b.json": {"type": "integer"}
+    });
+    let mut ctx = Context::default();
+    ctx.load_bundle(&bundle).expect("Invalid bundle");
+    let frozen = ctx.freeze().expect("References didn't resolve");
+    let a = uri("http://example.com/a.json");
+    assert!(frozen.validate(&a, &json!({"b": 1})).is_ok());
+    assert!(frozen.get(&a).expect("Schema wasn't kept").validate(&json!({"b": "x"})).is_err());
+
+    let mut ctx = frozen.thaw();
+    ctx.make_schema(uri("http://example.com/c.json"), &json!({
+        "items": {"$ref": "missing.json#/definitions/d"}
+    })).expect("Invalid schema");
+    assert_eq!(ctx.freeze(), Err(vec![uri("http://example.com/missing.json#/definitions/d")]));
+}
+
+#[test]
+fn dedup_subschemas_shares_identical_subschemas() {
+    let schema = json!({
+        "properties": {
+            "a": {"type": "string", "maxLength": 3},
+            "b": {"type": "string", "maxLength": 3},
+            "c": {"items": {"type": "string", "maxLength": 3}},
+            "d": {"type": "string", "maxLength": 4}
+        },
+        "additionalProperties": {"$ref": "#/properties/b"}
+    });
+
+    let mut plain = Context::new();
+    plain.make_schema(uri("http://example.com/a.json"), &schema).expect("Invalid schema");
+    let mut ctx = Context::new();
+    ctx.set_dedup_subschemas(true);
+    ctx.make_schema(uri("http://example.com/a.json"), &schema).expect("Invalid schema");
+    assert_eq!(ctx.len(), plain.len() - 2);
+    assert!(ctx.estimated_size() < plain.estimated_size());
+
+    // The shared subschemas are still found by their own URIs.
+    let b = ctx.get(&uri("http://example.com/a.json#/properties/b")).expect("Subschema wasn't found");
+    assert_eq!(b.id().as_str(), "http://example.com/a.json#/properties/b");
+    assert_eq!(b.to_value(), json!({"type": "string", "maxLength": 3}));
+    assert!(ctx.validate_references().is_ok());
+
+    assert!(ctx.equivalent(&plain));
+    assert!(plain.equivalent(&ctx));
+
+    let schema = ctx.get(&uri("http://example.com/a.json")).expect("Schema wasn't found");
+    assert_eq!(schema.to_value_full(), plain.get(&uri("http://example.com/a.json")).unwrap().to_value_full());
+    for value in &[json!({"a": "x", "b": "y", "c": ["z"], "d": "abcd", "e": "w"}), json!({"b": "long"}),
+                   json!({"c": ["long"]}), json!({"e": 1})] {
+        assert_eq!(schema.validate(value), plain.validate(&uri("http://example.com/a.json"), value));
+    }
+
+    // Replacing the schema the others share doesn't change them.
+    ctx.replace(uri("http://example.com/a.json#/properties/a"), &json!({"type": "integer"}))
+        .expect("Invalid schema");
+    assert!(ctx.validate(&uri("http://example.com/a.json"), &json!({"a": 1, "b": "y"})).is_ok());
+    assert!(ctx.validate(&uri("http://example.com/a.json"), &json!({"b": 1})).is_err());
+    assert!(ctx.validate(&uri("http://example.com/a.json"), &json!({"c": ["long"]})).is_err());
+}
@@ -0,0 +1,86 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{is_well_formed, BoundKind, Condition, Context, Type, ValidationError};
+
+#[test]
+fn conditions_display_concisely() {
+    assert_eq!(Condition::Minimum(5.into()).to_string(), "minimum 5");
+    assert_eq!(Condition::MaxLength(3).to_string(), "maxLength 3");
+    assert_eq!(Condition::Required(vec!["a".to_string(), "b".to_string()]).to_string(),
+        "required [a, b]");
+    assert_eq!(Condition::Type(vec![Type::Integer]).to_string(), "type integer");
+    assert_eq!(Condition::Type(vec![Type::Integer, Type::String]).to_string(),
+        "type [integer, string]");
+    assert_eq!(Condition::Enum(vec![json!(1), json!("a")]).to_string(), r#"enum [1, "a"]"#);
+}
+
+#[test]
+fn validation_errors_display_concisely() {
+    let err = ValidationError::NumberOutOfRange {
+        bound: 10.into(),
+        actual: 12.into(),
+        kind: BoundKind::Maximum,
+    };
+    assert_eq!(err.to_string(), "12 exceeds maximum 10");
+
+    let err = ValidationError::NumberOutOfRange {
+        bound: 0.into(),
+        actual: 0.into(),
+        kind: BoundKind::ExclusiveMinimum,
+    };
+    assert_eq!(err.to_string(), "0 is not greater than exclusiveMinimum 0");
+
+    let err = ValidationError::AllOfBranchFailed {
+        index: 1,
+        cause: Box::new(ValidationError::ConditionFailed(Condition::MaxLength(3))),
+    };
+    assert_eq!(err.to_string(), "allOf branch 1 failed: failed maxLength 3");
+
+    let err = ValidationError::OneOfAllFailed(vec![
+        ValidationError::ConditionFailed(Condition::Type(vec![Type::String])),
+        ValidationError::NoValuesPass(json!(1)),
+    ]);
+    assert_eq!(err.to_string(), "no oneOf branch matched: [failed type string; 1 given where no value can pass]");
+}
+
+#[test]
+fn validation_error_trees_render_nested_causes() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "anyOf": [
+            {"type": "string"},
+            {"additionalProperties": {"type": "integer"}},
+            {"allOf": [{"minProperties": 3}]}
+        ]
+    })).expect("Invalid schema");
+
+    let err = schema.validate(&json!({"a/b": "x"})).expect_err("No branch should match");
+    assert_eq!(err.render_tree(), r#"at root: no anyOf branch matched (schema: #/anyOf)
+  at root: failed type string (schema: #/anyOf/0/type)
+  at root: additional property "a/b" failed (schema: #/anyOf/1/additionalProperties)
+    at /a~1b: failed type integer (schema: #/anyOf/1/additionalProperties/type)
+  at root: allOf branch 0 failed (schema: #/anyOf/2/allOf/0)
+    at root: failed minProperties 3 (schema: #/anyOf/2/allOf/0/minProperties)
+"#);
+}
+
+#[test]
+fn unknown_types_list_the_valid_ones() {
+    let check = |schema| {
+        is_well_formed(&schema).expect_err("Unknown type was accepted").to_string()
+    };
+    let expected = "unknown type 'str', expected one of: null, boolean, number, integer, string, array, object";
+    assert_eq!(check(json!({"type": "str"})), expected);
+    assert_eq!(check(json!({"type": ["null", "str"]})), expected);
+    assert_eq!(check(json!({"minLength": -1})), "invalid value for minLength: -1");
+    assert_eq!(check(json!({"maximum": "10"})), r#"maximum has the wrong type: "10""#);
+
+    assert_eq!(Type::ALL.len(), 7);
+    assert!(Type::ALL.iter().all(|t| Type::from_string(t.as_str()) == Some(*t)));
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "draft7")]
+
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, is_valid};
+use json_schema::{Context, FromValueError};
+
+#[test]
+fn draft7_schema_uri_is_accepted() {
+    let schema = json!({"$schema": "http://json-schema.org/draft-07/schema#"});
+    assert!(is_valid(schema, json!(null)));
+}
+
+#[test]
+fn if_then_else() {
+    let schema = json!({
+        "if": {"type": "integer"},
+        "then": {"minimum": 0},
+        "else": {"type": "string"}
+    });
+    assert!(is_valid(schema.clone(), json!(5)));
+    assert!(!is_valid(schema.clone(), json!(-5)));
+    assert!(is_valid(schema.clone(), json!("x")));
+    assert!(!is_valid(schema, json!(null)));
+}
+
+#[test]
+fn if_without_then_or_else() {
+    let schema = json!({"if": {"type": "integer"}});
+    assert!(is_valid(schema.clone(), json!(5)));
+    assert!(is_valid(schema, json!("x")));
+
+    let schema = json!({"then": false, "else": false});
+    assert!(is_valid(schema, json!(5)));
+}
+
+#[test]
+fn read_only_and_write_only_are_annotations() {
+    let schema = json!({"readOnly": true, "writeOnly": false});
+    assert!(is_valid(schema, json!(5)));
+
+    let mut ctx = Context::default();
+    match ctx.make_schema(base_uri(), &json!({"readOnly": "yes"})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "readOnly"),
+        Err(err) => panic!("Wrong error: {:?}", err),
+        Ok(_) => panic!("Non-boolean readOnly was accepted"),
+    }
+}
+
+#[test]
+fn content_keywords_are_annotations() {
+    let schema = json!({
+        "contentMediaType": "application/json",
+        "contentEncoding": "base64"
+    });
+    assert!(is_valid(schema, json!("not base64 at all!")));
+}
+
+#[test]
+fn comment_is_an_annotation() {
+    let schema = json!({"$comment": "Anything goes.", "type": "string"});
+    assert!(is_valid(schema.clone(), json!("x")));
+    assert!(!is_valid(schema, json!(1)));
+}
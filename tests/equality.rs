@@ -0,0 +1,81 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, is_valid};
+use json_schema::{schemas_equivalent, Context};
+
+#[test]
+fn numbers_are_normalized() {
+    assert!(is_valid(json!({"const": [1, -2, 0]}), json!([1.0, -2.0, -0.0])));
+    assert!(is_valid(json!({"enum": [{"a": 2}]}), json!({"a": 2.0})));
+    assert!(!is_valid(json!({"const": 1}), json!(1.5)));
+    assert!(!is_valid(json!({"uniqueItems": true}), json!([[-1], [-1.0]])));
+    assert!(is_valid(json!({"uniqueItems": true}), json!([0.5, 1.5, 1])));
+}
+
+#[test]
+fn nested_object_keys_are_unordered() {
+    let a = json!({"x": {"b": [1, {"d": 1, "c": 2}], "a": null}});
+    let b = json!({"x": {"a": null, "b": [1, {"c": 2, "d": 1}]}});
+    assert!(is_valid(json!({"const": a.clone()}), b.clone()));
+    assert!(is_valid(json!({"enum": [1, a.clone()]}), b.clone()));
+    assert!(!is_valid(json!({"uniqueItems": true}), json!([a, b])));
+}
+
+#[test]
+fn different_values_are_unequal() {
+    let a = json!({"a": [1, 2]});
+    assert!(!is_valid(json!({"const": a.clone()}), json!({"a": [2, 1]})));
+    assert!(!is_valid(json!({"const": a.clone()}), json!({"a": [1, 2], "b": null})));
+    assert!(!is_valid(json!({"enum": ["1", [1]]}), json!(1)));
+    assert!(is_valid(json!({"uniqueItems": true}), json!([a, {"a": [2, 1]}, {"a": "[1,2]"}])));
+}
+
+#[test]
+fn const_and_enum_as_the_whole_schema() {
+    assert!(is_valid(json!({"const": [1, 2, 3]}), json!([1, 2, 3])));
+    assert!(!is_valid(json!({"const": [1, 2, 3]}), json!([1, 2])));
+    assert!(is_valid(json!({"enum": ["a", "b"]}), json!("a")));
+    assert!(!is_valid(json!({"enum": ["a", "b"]}), json!("c")));
+
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "allOf": [{"uniqueItems": true}],
+        "uniqueItems": true,
+        "enum": [[1], [2]]
+    })).expect("Invalid schema");
+    assert_eq!(schema.evaluation_order(), vec!["enum", "uniqueItems", "allOf"]);
+}
+
+#[test]
+fn schema_equivalence_ignores_order_and_annotations() {
+    let a = json!({
+        "title": "A",
+        "type": "object",
+        "properties": {"n": {"minimum": 0, "type": "integer"}},
+        "required": ["n"]
+    });
+    let b = json!({
+        "required": ["n"],
+        "properties": {"n": {"type": "integer", "description": "N.", "minimum": 0}},
+        "type": "object"
+    });
+    assert!(schemas_equivalent(&a, &b));
+    assert!(schemas_equivalent(&json!({}), &json!(true)));
+
+    let c = json!({
+        "type": "object",
+        "properties": {"n": {"type": "integer", "minimum": 1}},
+        "required": ["n"]
+    });
+    assert!(!schemas_equivalent(&a, &c));
+    assert!(!schemas_equivalent(&a, &json!({"type": 5})));
+
+    // Schemas that parse but fail the metaschema can still be compared.
+    let repeated = json!({"required": ["a", "a"]});
+    assert!(schemas_equivalent(&repeated, &repeated));
+}
@@ -0,0 +1,145 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{BoundKind, Condition, Context, Error, FromValueError, Type, ValidationError};
+use serde_json::Value;
+use std::error::Error as StdError;
+
+fn check(schema: &Value, json: &Value) -> Result<(), Error> {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema_unchecked(base_uri(), schema)?;
+    schema.validate(json)?;
+    Ok(())
+}
+
+#[test]
+fn both_error_kinds_convert_into_error() {
+    assert_eq!(check(&json!({"type": "integer"}), &json!(1)), Ok(()));
+
+    match check(&json!({"type": 1}), &json!(1)) {
+        Err(Error::FromValue(FromValueError::InvalidKeywordType(_, ref keyword, _))) => {
+            assert_eq!(keyword, "type")
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+
+    let err = check(&json!({"type": "integer"}), &json!("x")).expect_err("A string passed");
+    assert_eq!(err, Error::Validation(ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))));
+    assert_eq!(err.to_string(), "failed type integer");
+}
+
+fn describe<E: ::std::error::Error>(err: E) -> String {
+    err.to_string()
+}
+
+#[test]
+fn error_is_a_std_error() {
+    let err = Error::from(ValidationError::Io("broken pipe".to_string()));
+    assert_eq!(describe(err), "I/O error: broken pipe");
+}
+
+#[test]
+fn matches_kind_ignores_values() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "maximum": 10,
+        "additionalProperties": false
+    })).expect("Invalid schema");
+
+    let maximum = ValidationError::NumberOutOfRange {
+        bound: 0.into(),
+        actual: 0.into(),
+        kind: BoundKind::Maximum,
+    };
+    let err = schema.validate(&json!(12)).expect_err("12 passed");
+    assert!(err.matches_kind(&maximum));
+    assert!(err != maximum);
+    assert!(!err.matches_kind(&ValidationError::NumberOutOfRange {
+        bound: 10.into(),
+        actual: 12.into(),
+        kind: BoundKind::Minimum,
+    }));
+
+    let err = schema.validate(&json!({"a": 1})).expect_err("An extra property passed");
+    assert!(err.matches_kind(&ValidationError::AdditionalPropertyFailed {
+        key: "b".to_string(),
+        cause: Box::new(ValidationError::NoValuesPass(Value::Null)),
+    }));
+    assert!(!err.matches_kind(&ValidationError::AdditionalPropertyFailed {
+        key: "a".to_string(),
+        cause: Box::new(maximum.clone()),
+    }));
+
+    let min_length = ValidationError::ConditionFailed(Condition::MinLength(1));
+    assert!(min_length.matches_kind(&ValidationError::ConditionFailed(Condition::MinLength(5))));
+    assert!(!min_length.matches_kind(&ValidationError::ConditionFailed(Condition::MaxLength(1))));
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Point {
+    x: u8,
+    y: u8,
+}
+
+#[test]
+fn validate_into_deserializes_valid_values() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "type": "object",
+        "required": ["x", "y"],
+        "properties": {
+            "x": {"type": "integer", "minimum": 0},
+            "y": {"type": "integer", "minimum": 0}
+        }
+    })).expect("Invalid schema");
+
+    assert_eq!(schema.validate_into::<Point>(&json!({"x": 1, "y": 2})), Ok(Point { x: 1, y: 2 }));
+
+    // Rejected by the schema, so deserialization is never attempted.
+    match schema.validate_into::<Point>(&json!({"x": 1})) {
+        Err(Error::Validation(ref err)) => {
+            assert!(err.matches_kind(&ValidationError::ConditionFailed(Condition::Required(vec![]))))
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match schema.validate_into::<Point>(&json!({"x": -1, "y": 2})) {
+        Err(Error::Validation(ref err)) => assert!(err.matches_kind(&ValidationError::NumberOutOfRange {
+            bound: 0.into(),
+            actual: 0.into(),
+            kind: BoundKind::Minimum,
+        })),
+        r => panic!("Wrong result: {:?}", r),
+    }
+
+    // Valid, but out of range for a u8.
+    match schema.validate_into::<Point>(&json!({"x": 300, "y": 2})) {
+        Err(err @ Error::Deserialize(_)) => {
+            let source = err.source().expect("No source");
+            assert!(source.downcast_ref::<serde_json::Error>().is_some());
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn errors_have_sources() {
+    let err = check(&json!({"pattern": "("}), &json!("x")).expect_err("A bad pattern parsed");
+    let source = err.source().expect("No source");
+    let source = source.downcast_ref::<FromValueError>().expect("Wrong source");
+    assert!(source.source().is_some());
+
+    let err = check(&json!({"allOf": [{"type": "integer"}]}), &json!("x")).expect_err("A string passed");
+    let source = err.source().expect("No source");
+    let source = source.downcast_ref::<ValidationError>().expect("Wrong source");
+    let cause = source.source().expect("No cause");
+    assert_eq!(cause.downcast_ref::<ValidationError>(),
+        Some(&ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))));
+    assert!(cause.source().is_none());
+}
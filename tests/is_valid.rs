@@ -0,0 +1,75 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::Context;
+
+#[test]
+fn is_valid_agrees_with_validate() {
+    let schema = json!({
+        "type": "object",
+        "required": ["id"],
+        "properties": {
+            "id": {"type": "integer", "minimum": 1},
+            "tags": {"items": {"enum": ["a", "b"]}, "uniqueItems": true},
+            "kind": {"oneOf": [{"const": "x"}, {"type": "integer"}]}
+        },
+        "patternProperties": {"^x-": {"type": "string"}},
+        "additionalProperties": false,
+        "dependencies": {"kind": ["tags"], "tags": {"maxProperties": 3}},
+        "propertyNames": {"maxLength": 4},
+        "anyOf": [{"required": ["tags"]}, {"not": {"required": ["kind"]}}]
+    });
+    let values = vec![
+        json!({"id": 1}),
+        json!({"id": 0}),
+        json!({"id": 1, "tags": ["a", "b"]}),
+        json!({"id": 1, "tags": ["a", "a"]}),
+        json!({"id": 1, "tags": ["c"]}),
+        json!({"id": 1, "tags": [], "kind": "x"}),
+        json!({"id": 1, "tags": [], "kind": 2}),
+        json!({"id": 1, "kind": "x"}),
+        json!({"id": 1, "tags": [], "kind": "y"}),
+        json!({"id": 1, "x-a": "s"}),
+        json!({"id": 1, "x-a": 1}),
+        json!({"id": 1, "other": 1}),
+        json!({"id": 1, "tags": [], "x-abc": "s"}),
+        json!({"id": 1, "tags": [], "x-a": "s", "x-b": "t"}),
+        json!([]),
+        json!(null),
+    ];
+
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &schema)
+        .expect("Invalid schema");
+    for value in values {
+        assert_eq!(schema.is_valid(&value), schema.validate(&value).is_ok(), "{}", value);
+    }
+
+    // References that don't resolve are errors, which `not` doesn't invert.
+    let bad = json!({"$ref": "http://nowhere/x"});
+    let schemas = vec![
+        json!({"not": bad}),
+        json!({"not": {"anyOf": [bad, {"type": "string"}]}}),
+        json!({"not": {"oneOf": [bad, {"type": "string"}]}}),
+        json!({"not": {"oneOf": [{"type": "integer"}, {"type": "number"}, bad]}}),
+        json!({"not": {"allOf": [{"type": "integer"}, bad]}}),
+        json!({"not": {"contains": bad}}),
+        json!({"not": {"items": bad}}),
+        json!({"not": {"not": bad}}),
+        json!({"anyOf": [{"type": "integer"}, {"not": bad}]}),
+        json!({"not": {"allOf": [{"$ref": "#"}]}}),
+    ];
+    for schema in schemas {
+        let mut ctx = Context::default();
+        let schema = ctx.make_schema(base_uri(), &schema).expect("Invalid schema");
+        for value in &[json!(1), json!("x"), json!([1]), json!([])] {
+            assert_eq!(schema.is_valid(value), schema.validate(value).is_ok(), "{} against {}",
+                value, schema.to_value());
+        }
+    }
+}
@@ -0,0 +1,182 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{Condition, Context, FromValueError, Type, ValidationError};
+
+#[test]
+fn tuple_items_leave_extra_elements_unconstrained() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "items": [{"type": "integer"}]
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!([1, "anything", null])).is_ok());
+}
+
+#[test]
+fn tuple_items_constrain_extra_elements_with_additional_items() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "items": [{"type": "integer"}],
+        "additionalItems": {"type": "integer"}
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!([1, 2, 3])).is_ok());
+    assert!(schema.validate(&json!([1, "anything", null])).is_err());
+}
+
+#[test]
+fn tuple_items_with_a_rest_schema() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "items": [{"type": "integer"}],
+        "additionalItems": {"type": "string"}
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!([1, "a", "b"])).is_ok());
+    assert!(schema.is_valid(&json!([1, "a", "b"])));
+
+    assert_eq!(schema.validate(&json!([1, "a", 2])),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))));
+    assert!(!schema.is_valid(&json!([1, "a", 2])));
+    assert_eq!(schema.validate_tree(&json!([1, "a", 2])).to_flat_vec(), vec![
+        ("/2".to_string(), ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))),
+    ]);
+
+    assert_eq!(schema.validate(&json!(["x"])),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))));
+    assert!(!schema.is_valid(&json!(["x"])));
+    assert_eq!(schema.validate_tree(&json!(["x"])).to_flat_vec(), vec![
+        ("/0".to_string(), ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))),
+    ]);
+}
+
+#[test]
+fn tuple_items_longer_than_the_array() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "items": [{"type": "integer"}, {"type": "string"}]
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!([])).is_ok());
+    assert!(schema.validate(&json!([1])).is_ok());
+    assert!(schema.validate(&json!([1, "a"])).is_ok());
+    assert_eq!(schema.validate(&json!([1, 2])),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))));
+    assert!(!schema.is_valid(&json!([1, 2])));
+}
+
+#[test]
+fn unique_items() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"uniqueItems": true}))
+        .expect("Invalid schema");
+
+    assert!(schema.validate(&json!([1, 2, "1", [1], {"a": 1}])).is_ok());
+    assert!(schema.validate(&json!([1, 2, 1.0])).is_err());
+    assert!(schema.validate(&json!([{"a": 1, "b": [0]}, {"b": [-0.0], "a": 1}])).is_err());
+    assert!(schema.validate(&json!("not an array")).is_ok());
+}
+
+#[test]
+fn unique_items_on_large_array() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"uniqueItems": true}))
+        .expect("Invalid schema");
+
+    let mut arr = (0..10000).map(|i| json!({
+        "id": i,
+        "nested": {"tags": ["a", "b"], "depth": {"value": i}}
+    })).collect::<Vec<_>>();
+    assert!(schema.validate(&serde_json::Value::Array(arr.clone())).is_ok());
+
+    arr.push(json!({
+        "nested": {"depth": {"value": 1234.0}, "tags": ["a", "b"]},
+        "id": 1234
+    }));
+    assert!(schema.validate(&serde_json::Value::Array(arr)).is_err());
+}
+
+#[test]
+fn count_keywords_accept_integral_floats() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"maxItems": 1e3}))
+        .expect("Invalid schema");
+
+    assert!(schema.validate(&serde_json::Value::Array(vec![json!(0); 1000])).is_ok());
+    assert!(schema.validate(&serde_json::Value::Array(vec![json!(0); 1001])).is_err());
+
+    match ctx.make_schema_unchecked(base_uri(), &json!({"maxItems": 2.5})) {
+        Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "maxItems"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn count_keywords_reject_negative_and_fractional_values() {
+    let mut ctx = Context::default();
+    for value in &[json!(-1), json!(1.5)] {
+        match ctx.make_schema_unchecked(base_uri(), &json!({"minItems": value})) {
+            Err(FromValueError::InvalidKeywordValue(_, ref keyword, ref v)) => {
+                assert_eq!(keyword, "minItems");
+                assert_eq!(v, value);
+            },
+            r => panic!("Wrong result: {:?}", r),
+        }
+    }
+    match ctx.make_schema_unchecked(base_uri(), &json!({"minItems": "3"})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "minItems"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn contains_reports_every_element_error() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"contains": {"type": "string"}}))
+        .expect("Invalid schema");
+
+    assert!(schema.validate(&json!([1, "x"])).is_ok());
+
+    let failed = ValidationError::ConditionFailed(Condition::Type(vec![Type::String]));
+    let err = schema.validate(&json!([1, 2, 3])).expect_err("No element was a string");
+    assert_eq!(err, ValidationError::ContainsNoneMatched(vec![failed.clone(), failed.clone(), failed]));
+    assert_eq!(err.to_string(), "no array element matched (element 0: failed type string; \
+        element 1: failed type string; element 2: failed type string)");
+
+    let err = schema.validate(&json!([])).expect_err("Empty array passed contains");
+    assert_eq!(err, ValidationError::ContainsNoneMatched(Vec::new()));
+}
+
+#[test]
+#[cfg(not(feature = "contains-count"))]
+fn contains_counts_need_the_feature() {
+    let mut ctx = Context::default();
+    ctx.set_strict_keywords(true);
+    match ctx.make_schema_unchecked(base_uri(), &json!({"contains": {}, "minContains": 2})) {
+        Err(FromValueError::UnknownKeyword(_, ref keyword)) => assert_eq!(keyword, "minContains"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn items_must_be_a_schema_or_array() {
+    let mut ctx = Context::default();
+    match ctx.make_schema_unchecked(base_uri(), &json!({"items": 5})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, ref value)) => {
+            assert_eq!(keyword, "items");
+            assert_eq!(value, &json!(5));
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match ctx.make_schema_unchecked(base_uri(), &json!({"items": [{}], "additionalItems": "no"})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "additionalItems"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+    assert!(ctx.make_schema_unchecked(base_uri(), &json!({"items": false})).is_ok());
+}
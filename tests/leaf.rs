@@ -0,0 +1,63 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::is_valid;
+use json_schema::{LeafCondition, LeafValue, Type};
+use std::collections::BTreeMap;
+
+#[test]
+fn leaf_conditions_hold() {
+    let five = LeafValue::Number(5.0);
+    assert!(LeafCondition::Type(vec![Type::Integer]).holds(&five));
+    assert!(!LeafCondition::Type(vec![Type::Integer]).holds(&LeafValue::Number(5.5)));
+    assert!(LeafCondition::Type(vec![Type::Null, Type::String]).holds(&LeafValue::Null));
+    assert!(LeafCondition::Maximum(5.0).holds(&five));
+    assert!(!LeafCondition::ExclusiveMaximum(5.0).holds(&five));
+    assert!(LeafCondition::MultipleOf(0.1).holds(&LeafValue::Number(0.3)));
+    assert!(!LeafCondition::MultipleOf(2.0).holds(&five));
+    assert!(LeafCondition::MultipleOf(0.5).holds(&LeafValue::Number(2000000000.5)));
+    assert!(!LeafCondition::MultipleOf(2.0).holds(&LeafValue::Number(1000000001.0)));
+    assert!(!LeafCondition::MultipleOf(1.5).holds(&LeafValue::Number(4000000001.0)));
+    assert!(!LeafCondition::MultipleOf(0.5).holds(&LeafValue::Number(-2000000000.3)));
+
+    // Conditions on other types pass.
+    let s = LeafValue::String("héllo".to_string());
+    assert!(LeafCondition::Minimum(10.0).holds(&s));
+    assert!(LeafCondition::MaxLength(5).holds(&s));
+    assert!(!LeafCondition::MinLength(6).holds(&s));
+
+    let mut obj = BTreeMap::new();
+    obj.insert("a".to_string(), LeafValue::Array(vec![five.clone(), LeafValue::Bool(true)]));
+    let obj = LeafValue::Object(obj);
+    assert!(LeafCondition::Required(vec!["a".to_string()]).holds(&obj));
+    assert!(!LeafCondition::Required(vec!["b".to_string()]).holds(&obj));
+    assert!(LeafCondition::Const(obj.clone()).holds(&obj));
+    assert!(LeafCondition::Enum(vec![LeafValue::Null, five.clone()]).holds(&five));
+    assert!(!LeafCondition::Enum(vec![LeafValue::Null, five]).holds(&obj));
+}
+
+#[test]
+fn leaf_conditions_agree_with_schemas() {
+    let values = vec![json!(null), json!(1), json!(1.0), json!(2.5), json!("ab"), json!([1]), json!({"a": 1})];
+    let cases = vec![
+        (LeafCondition::Type(vec![Type::Integer]), json!({"type": "integer"})),
+        (LeafCondition::Maximum(1.0), json!({"maximum": 1})),
+        (LeafCondition::ExclusiveMinimum(1.0), json!({"exclusiveMinimum": 1})),
+        (LeafCondition::MultipleOf(0.5), json!({"multipleOf": 0.5})),
+        (LeafCondition::MinLength(3), json!({"minLength": 3})),
+        (LeafCondition::MinItems(1), json!({"minItems": 1})),
+        (LeafCondition::MaxProperties(0), json!({"maxProperties": 0})),
+        (LeafCondition::Const(LeafValue::from(&json!(1))), json!({"const": 1})),
+        (LeafCondition::Enum(vec![LeafValue::from(&json!({"a": 1}))]), json!({"enum": [{"a": 1}]})),
+    ];
+    for (condition, schema) in cases {
+        for value in &values {
+            assert_eq!(condition.holds(&LeafValue::from(value)), is_valid(schema.clone(), value.clone()),
+                "{:?} disagrees with {} on {}", condition, schema, value);
+        }
+    }
+}
@@ -1,9 +1,65 @@
+#![cfg(feature = "metaschema")]
+
 extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, uri};
+use json_schema::{validate_schema, Context, FromValueError, METASCHEMA_URI, METASCHEMA_VALUE};
 
-#[ignore] // TODO Reenable
 #[test]
 fn metaschema_validates_itself() {
-    use json_schema::metaschema::{METASCHEMA, METASCHEMA_VALUE};
+    let ctx = Context::new();
+    assert!(ctx.validate(&METASCHEMA_URI, &METASCHEMA_VALUE).is_ok());
+}
 
-    assert!(METASCHEMA.validate(&METASCHEMA_VALUE).is_ok());
+#[test]
+fn make_schema_checks_against_metaschema() {
+    let mut ctx = Context::new();
+    match ctx.make_schema(base_uri(), &json!({"minLength": -1})) {
+        Err(FromValueError::MetaschemaFailedToValidate(_)) => {},
+        Err(err) => panic!("Wrong error: {:?}", err),
+        Ok(_) => panic!("Negative minLength was accepted"),
+    }
+    assert!(ctx.make_schema(base_uri(), &json!({"minLength": 1})).is_ok());
+}
+
+#[test]
+fn make_schema_unchecked_skips_metaschema() {
+    let mut ctx = Context::new();
+    let schema = ctx.make_schema_unchecked(base_uri(), &json!({"required": ["a", "a"]}))
+        .expect("Invalid schema");
+    assert!(schema.validate(&json!({"a": 1})).is_ok());
+}
+
+#[test]
+fn refs_into_the_metaschema_resolve() {
+    let mut ctx = Context::new();
+    ctx.make_schema(base_uri(), &json!({
+        "properties": {
+            "tags": {"$ref": "http://json-schema.org/draft-06/schema#/definitions/stringArray"}
+        }
+    })).expect("Invalid schema");
+    assert!(ctx.validate_references().is_ok());
+    assert!(ctx.validate(&base_uri(), &json!({"tags": ["a", "b"]})).is_ok());
+    assert!(ctx.validate(&base_uri(), &json!({"tags": ["a", "a"]})).is_err());
+    assert!(ctx.validate(&base_uri(), &json!({"tags": [1]})).is_err());
+
+    assert!(ctx.get(&uri("http://json-schema.org/draft-06/schema#/definitions/nonNegativeInteger")).is_some());
+}
+
+#[test]
+fn validate_schema_checks_against_metaschema() {
+    assert_eq!(validate_schema(&json!({
+        "type": "object",
+        "properties": {"name": {"type": "string"}},
+        "required": ["name"]
+    })), Ok(()));
+    assert_eq!(validate_schema(&json!(true)), Ok(()));
+    assert!(validate_schema(&json!({"type": 42})).is_err());
+    assert!(validate_schema(&json!({"required": ["a", "a"]})).is_err());
+    assert!(validate_schema(&json!(1)).is_err());
 }
@@ -0,0 +1,218 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, is_valid};
+use json_schema::{BoundKind, Context, FromValueError, ValidationError};
+
+#[test]
+fn draft4_boolean_exclusive_bounds() {
+    let mut ctx = Context::default();
+    ctx.set_draft4_exclusive_bounds(true);
+    let schema = ctx.make_schema_unchecked(base_uri(), &json!({
+        "maximum": 10,
+        "exclusiveMaximum": true,
+        "minimum": 0,
+        "exclusiveMinimum": false
+    })).expect("Invalid schema");
+
+    assert!(schema.validate(&json!(9)).is_ok());
+    assert!(schema.validate(&json!(0)).is_ok());
+    assert!(schema.validate(&json!(10)).is_err());
+    assert!(schema.validate(&json!(-1)).is_err());
+}
+
+#[test]
+fn draft4_boolean_exclusive_bounds_rejected_by_default() {
+    let mut ctx = Context::default();
+    match ctx.make_schema_unchecked(base_uri(), &json!({"maximum": 10, "exclusiveMaximum": true})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "exclusiveMaximum"),
+        Err(err) => panic!("Wrong error: {:?}", err),
+        Ok(_) => panic!("Boolean exclusiveMaximum was accepted"),
+    }
+}
+
+#[test]
+fn draft6_numeric_exclusive_bounds() {
+    assert!(is_valid(json!({"exclusiveMaximum": 10}), json!(9)));
+    assert!(!is_valid(json!({"exclusiveMaximum": 10}), json!(10)));
+    assert!(is_valid(json!({"exclusiveMinimum": 0}), json!(1)));
+    assert!(!is_valid(json!({"exclusiveMinimum": 0}), json!(0)));
+}
+
+#[test]
+fn bound_errors_include_the_failing_number() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"maximum": 10}))
+        .expect("Invalid schema");
+
+    let err = schema.validate(&json!(12)).expect_err("12 passed maximum 10");
+    assert_eq!(err, ValidationError::NumberOutOfRange {
+        bound: 10.into(),
+        actual: 12.into(),
+        kind: BoundKind::Maximum,
+    });
+    assert_eq!(err.to_string(), "12 exceeds maximum 10");
+}
+
+#[test]
+fn bounds_compare_across_representations() {
+    assert!(is_valid(json!({"maximum": 10}), json!(10.0)));
+    assert!(!is_valid(json!({"maximum": 10}), json!(10.5)));
+    assert!(is_valid(json!({"maximum": 10.5}), json!(10)));
+    assert!(!is_valid(json!({"maximum": 9.5}), json!(10)));
+    assert!(is_valid(json!({"minimum": 0}), json!(0)));
+    assert!(is_valid(json!({"minimum": 0}), json!(-0.0)));
+    assert!(!is_valid(json!({"minimum": 0}), json!(-1)));
+    assert!(!is_valid(json!({"exclusiveMinimum": 0}), json!(0.0)));
+    assert!(is_valid(json!({"exclusiveMaximum": 0.5}), json!(0)));
+
+    // These differ by less than a float can represent at this size.
+    assert!(!is_valid(json!({"maximum": 9007199254740992.0}), json!(9007199254740993u64)));
+    assert!(is_valid(json!({"exclusiveMaximum": 18446744073709551616.0}), json!(18446744073709551615u64)));
+    assert!(is_valid(json!({"minimum": -1}), json!(18446744073709551615u64)));
+    assert!(!is_valid(json!({"maximum": -1}), json!(18446744073709551615u64)));
+    assert!(is_valid(json!({"maximum": 18446744073709551615u64}), json!(18446744073709551615u64)));
+    assert!(!is_valid(json!({"maximum": 18446744073709551614u64}), json!(18446744073709551615u64)));
+}
+
+#[test]
+fn integer_bounds_compare_exactly_past_two_to_the_53() {
+    // Past 2^53, floats only represent even integers, so 2^53 + 1 rounds to
+    // 2^53; these only hold if integers are compared as integers.
+    assert!(is_valid(json!({"maximum": 9007199254740993u64}), json!(9007199254740993u64)));
+    assert!(!is_valid(json!({"maximum": 9007199254740993u64}), json!(9007199254740994u64)));
+    assert!(!is_valid(json!({"minimum": 9007199254740993u64}), json!(9007199254740992u64)));
+    assert!(!is_valid(json!({"exclusiveMaximum": 9007199254740993u64}), json!(9007199254740993u64)));
+    assert!(is_valid(json!({"exclusiveMinimum": 9007199254740992u64}), json!(9007199254740993u64)));
+    assert!(!is_valid(json!({"minimum": -9007199254740993i64}), json!(-9007199254740994i64)));
+    assert!(is_valid(json!({"exclusiveMaximum": -9007199254740992i64}), json!(-9007199254740993i64)));
+
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"maximum": 9007199254740993u64}))
+        .expect("Invalid schema");
+    assert!(!schema.is_valid(&json!(9007199254740994u64)));
+    assert_eq!(schema.validate(&json!(9007199254740994u64)), Err(ValidationError::NumberOutOfRange {
+        bound: 9007199254740993u64.into(),
+        actual: 9007199254740994u64.into(),
+        kind: BoundKind::Maximum,
+    }));
+}
+
+#[test]
+fn const_compares_numbers_by_value() {
+    assert!(is_valid(json!({"const": 0}), json!(0)));
+    assert!(is_valid(json!({"const": 0}), json!(0.0)));
+    assert!(is_valid(json!({"const": 0}), json!(-0.0)));
+    assert!(is_valid(json!({"const": 1.0}), json!(1)));
+    assert!(!is_valid(json!({"const": 1}), json!(1.5)));
+    assert!(is_valid(json!({"const": {"a": [1, 2.0]}}), json!({"a": [1.0, 2]})));
+}
+
+#[test]
+fn enum_compares_numbers_by_value() {
+    assert!(is_valid(json!({"enum": [1]}), json!(1)));
+    assert!(is_valid(json!({"enum": [1]}), json!(1.0)));
+    assert!(is_valid(json!({"enum": ["a", -0.0]}), json!(0)));
+    assert!(!is_valid(json!({"enum": [1]}), json!(2)));
+    assert!(!is_valid(json!({"enum": [1]}), json!("1")));
+    assert!(!is_valid(json!({"enum": [-1]}), json!(18446744073709551615u64)));
+}
+
+#[test]
+fn integral_floats_are_integers() {
+    assert!(is_valid(json!({"type": "integer"}), json!(1.0)));
+    assert!(is_valid(json!({"type": "integer"}), json!(-1e3)));
+    assert!(!is_valid(json!({"type": "integer"}), json!(1.5)));
+}
+
+#[test]
+fn large_integral_floats_are_integers() {
+    assert!(is_valid(json!({"type": "integer"}), json!(1e20)));
+    assert!(is_valid(json!({"type": "integer"}), json!(-1e20)));
+    assert!(is_valid(json!({"type": "integer"}), json!(1.23e4)));
+    assert!(!is_valid(json!({"type": "integer"}), json!(1.2345e2)));
+
+    let big = serde_json::from_str("100000000000000000000").expect("Invalid JSON");
+    assert!(is_valid(json!({"type": "integer"}), big));
+}
+
+#[test]
+fn multiple_of_rejects_large_non_multiples() {
+    assert!(is_valid(json!({"multipleOf": 0.1}), json!(0.3)));
+    assert!(is_valid(json!({"multipleOf": 0.01}), json!(19.99)));
+    assert!(is_valid(json!({"multipleOf": 0.5}), json!(2000000000.5)));
+    assert!(is_valid(json!({"multipleOf": 1.5}), json!(4000000000.5)));
+    assert!(is_valid(json!({"multipleOf": 2}), json!(10000000002.0)));
+
+    assert!(!is_valid(json!({"multipleOf": 2}), json!(1000000001.0)));
+    assert!(!is_valid(json!({"multipleOf": 2}), json!(10000000001.0)));
+    assert!(!is_valid(json!({"multipleOf": 1.5}), json!(4000000001u64)));
+    assert!(!is_valid(json!({"multipleOf": 0.5}), json!(2000000000.3)));
+    assert!(!is_valid(json!({"multipleOf": 0.1}), json!(123456789012.35)));
+    assert!(!is_valid(json!({"multipleOf": 0.7}), json!(-7000000001i64)));
+}
+
+#[test]
+fn maximum_and_exclusive_maximum_apply_independently() {
+    assert!(is_valid(json!({"maximum": 10}), json!(10)));
+    assert!(!is_valid(json!({"maximum": 10, "exclusiveMaximum": 10}), json!(10)));
+    assert!(is_valid(json!({"maximum": 10, "exclusiveMaximum": 10}), json!(9.5)));
+    assert!(!is_valid(json!({"maximum": 5, "exclusiveMaximum": 10}), json!(7)));
+    assert!(!is_valid(json!({"minimum": 0, "exclusiveMinimum": 0}), json!(0)));
+}
+
+#[test]
+fn max_length_and_exclusive_maximum_apply_independently() {
+    let schema = json!({"maxLength": 2, "exclusiveMaximum": 2});
+    assert!(is_valid(schema.clone(), json!("ab")));
+    assert!(!is_valid(schema.clone(), json!("abc")));
+    assert!(is_valid(schema.clone(), json!(1)));
+    assert!(!is_valid(schema, json!(2)));
+}
+
+#[test]
+fn validate_only_maximum() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({"type": "integer", "maximum": 10}))
+        .expect("Invalid schema");
+
+    assert_eq!(schema.validate_keyword("maximum", &json!(5)), Some(Ok(())));
+    assert_eq!(schema.validate_keyword("maximum", &json!(12)), Some(Err(ValidationError::NumberOutOfRange {
+        bound: 10.into(),
+        actual: 12.into(),
+        kind: BoundKind::Maximum,
+    })));
+    // The string isn't an integer, but `maximum` ignores non-numbers.
+    assert_eq!(schema.validate_keyword("maximum", &json!("x")), Some(Ok(())));
+    assert!(schema.validate_keyword("type", &json!("x")).expect("No type keyword").is_err());
+    assert_eq!(schema.validate_keyword("minimum", &json!(5)), None);
+}
+
+#[test]
+fn numeric_keywords_round_trip_as_written() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "multipleOf": 2.0,
+        "maximum": 5,
+        "exclusiveMinimum": -1.5,
+        "minimum": 18446744073709551615u64
+    })).expect("Invalid schema");
+
+    let value = schema.to_value();
+    assert!(value["multipleOf"].is_f64());
+    assert!(value["maximum"].is_u64());
+    assert!(value["minimum"].is_u64());
+    let text = value.to_string();
+    assert!(text.contains(r#""multipleOf":2.0"#), "{}", text);
+    assert!(text.contains(r#""maximum":5,"#), "{}", text);
+    assert!(text.contains(r#""exclusiveMinimum":-1.5"#), "{}", text);
+    assert!(text.contains(r#""minimum":18446744073709551615"#), "{}", text);
+
+    let mut reparsed = Context::default();
+    let schema = reparsed.make_schema(base_uri(), &value).expect("Invalid schema");
+    assert_eq!(schema.to_value(), value);
+}
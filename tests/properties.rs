@@ -0,0 +1,204 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, make_schema};
+use json_schema::{Condition, Context, FromValueError, Type, ValidationError};
+
+#[test]
+fn pattern_properties_without_properties() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "patternProperties": {"^x-": {"type": "string"}}
+    }));
+
+    assert!(schema.validate(&json!({"x-foo": "a"})).is_ok());
+    assert!(schema.validate(&json!({"x-foo": 1})).is_err());
+    assert!(schema.validate(&json!({"foo": 1})).is_ok());
+}
+
+#[test]
+fn key_matching_several_patterns_must_satisfy_all() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "patternProperties": {
+            "^a": {"type": "integer"},
+            "b$": {"minimum": 10}
+        }
+    }));
+
+    assert!(schema.validate(&json!({"ab": 20})).is_ok());
+    assert!(schema.validate(&json!({"ab": 5})).is_err());
+    assert!(schema.validate(&json!({"ab": "x"})).is_err());
+    assert!(schema.validate(&json!({"a": "x", "b": 10})).is_err());
+    assert!(schema.validate(&json!({"a": 5, "b": 10})).is_ok());
+}
+
+#[test]
+fn pattern_and_named_property_both_apply() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "properties": {
+            "foo": {"type": "integer"}
+        },
+        "patternProperties": {
+            "^f": {"minimum": 10}
+        }
+    }));
+
+    assert!(schema.validate(&json!({"foo": 15})).is_ok());
+    assert!(schema.validate(&json!({"foo": 5})).is_err());
+    assert!(schema.validate(&json!({"foo": 15.5})).is_err());
+}
+
+#[test]
+fn matching_pattern_is_not_additional() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "patternProperties": {
+            "^x-": {},
+            "-y$": {}
+        },
+        "additionalProperties": false
+    }));
+
+    assert!(schema.validate(&json!({"x-a": 1, "b-y": 2, "x-y": 3})).is_ok());
+    assert!(schema.validate(&json!({"x-a": 1, "z": 2})).is_err());
+}
+
+#[test]
+fn false_additional_properties_rejects_unnamed_keys() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "properties": {"foo": {}},
+        "additionalProperties": false
+    }));
+
+    assert!(schema.validate(&json!({"foo": 1})).is_ok());
+    assert!(schema.validate(&json!({})).is_ok());
+    assert!(schema.validate(&json!({"foo": 1, "bar": 2})).is_err());
+}
+
+#[test]
+fn additional_property_errors_name_the_key() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "properties": {"foo": {"type": "string"}},
+        "additionalProperties": {"type": "integer"}
+    }));
+
+    let err = schema.validate(&json!({"foo": "a", "bar": "b"})).expect_err("bar isn't an integer");
+    assert_eq!(err, ValidationError::AdditionalPropertyFailed {
+        key: "bar".to_string(),
+        cause: Box::new(ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))),
+    });
+    assert_eq!(err.to_string(), r#"additional property "bar" failed: failed type integer"#);
+
+    assert_eq!(schema.validate(&json!({"foo": 1})),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))));
+}
+
+#[test]
+fn additional_properties_schema_applies_only_to_unnamed_keys() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "properties": {"foo": {}},
+        "patternProperties": {"^v": {"type": "string"}},
+        "additionalProperties": {"type": "integer"}
+    }));
+
+    assert!(schema.validate(&json!({"bar": 3})).is_ok());
+    assert!(schema.validate(&json!({"bar": "x"})).is_err());
+    assert!(schema.validate(&json!({"foo": "x", "vbar": "y", "bar": 3})).is_ok());
+    assert!(schema.validate(&json!({"vbar": 3})).is_err());
+}
+
+#[test]
+fn dependencies_mixing_properties_and_schemas() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "dependencies": {
+            "a": ["b", "c"],
+            "b": {"required": ["d"]}
+        }
+    }));
+
+    assert!(schema.validate(&json!({})).is_ok());
+    assert!(schema.validate(&json!({"a": 1, "b": 2, "c": 3, "d": 4})).is_ok());
+    assert!(schema.validate(&json!({"a": 1, "b": 2, "d": 4})).is_err());
+    assert!(schema.validate(&json!({"b": 2})).is_err());
+    assert!(schema.validate(&json!({"b": 2, "d": 4})).is_ok());
+}
+
+#[test]
+fn dependencies_rejects_bad_property_lists() {
+    for deps in &[json!({"a": ["a", "a"]}), json!({"a": ["b", 1]})] {
+        let mut ctx = Context::default();
+        match ctx.make_schema_unchecked(base_uri(), &json!({"dependencies": deps})) {
+            Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "dependencies"),
+            r => panic!("Wrong result: {:?}", r),
+        }
+    }
+}
+
+#[test]
+fn property_names_are_validated_as_strings() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"propertyNames": {"pattern": "^[a-z]+$"}}));
+    assert!(schema.validate(&json!({"foo": 1})).is_ok());
+    assert!(schema.validate(&json!({"Foo": 1})).is_err());
+    assert!(schema.validate(&json!("Foo")).is_ok());
+
+    let schema = make_schema(&mut ctx, json!({"propertyNames": {"type": "integer"}}));
+    assert!(schema.validate(&json!({})).is_ok());
+    assert!(schema.validate(&json!({"1": 1})).is_err());
+}
+
+#[test]
+fn property_names_apply_alongside_properties() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "propertyNames": {"maxLength": 3},
+        "properties": {"abcd": {}}
+    }));
+    assert_eq!(schema.evaluation_order(), vec!["properties", "propertyNames"]);
+    assert!(schema.validate(&json!({"abc": 1})).is_ok());
+    assert!(schema.validate(&json!({"abcd": 1})).is_err());
+    assert!(!schema.is_valid(&json!({"abcd": 1})));
+
+    let schema = make_schema(&mut ctx, json!({
+        "propertyNames": {"maxLength": 3},
+        "properties": {"a": {"type": "integer"}},
+        "additionalProperties": {"type": "string"}
+    }));
+    assert!(schema.validate(&json!({"a": 1, "b": "x"})).is_ok());
+    assert!(schema.validate(&json!({"a": "x"})).is_err());
+    assert!(schema.validate(&json!({"b": 1})).is_err());
+    assert!(schema.validate(&json!({"long": "x"})).is_err());
+    assert_eq!(schema.validate_all(&json!({"long": 1})).map_err(|errs| errs.len()), Err(2));
+}
+
+#[test]
+fn additional_properties_referencing_a_definition() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "definitions": {
+            "value": {"type": "object", "required": ["amount"], "properties": {"amount": {"type": "number"}}}
+        },
+        "type": "object",
+        "additionalProperties": {"$ref": "#/definitions/value"}
+    }));
+
+    assert!(schema.validate(&json!({})).is_ok());
+    assert!(schema.validate(&json!({"a": {"amount": 1}, "b": {"amount": 2.5}})).is_ok());
+    assert!(schema.validate(&json!({"a": {"amount": 1}, "b": {"amount": "2"}})).is_err());
+    assert!(schema.validate(&json!({"a": {}})).is_err());
+    assert!(!schema.is_valid(&json!({"a": 1})));
+    assert_eq!(schema.validate_tree(&json!({"a": {"amount": 1}, "b": {}})).to_flat_vec(), vec![
+        ("/b".to_string(), ValidationError::ConditionFailed(Condition::Required(vec!["amount".to_string()]))),
+    ]);
+    assert!(ctx.validate_references().is_ok());
+}
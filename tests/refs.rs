@@ -0,0 +1,344 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, make_schema, uri};
+use json_schema::{Context, FromValueError, ValidationError};
+
+#[test]
+fn ref_to_property_with_special_characters() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "properties": {
+            "a/b~c": {"type": "integer"},
+            "a b%": {"type": "string"},
+            "slash": {"$ref": "#/properties/a~1b~0c"},
+            "space": {"$ref": "#/properties/a%20b%25"}
+        }
+    }));
+
+    assert!(schema.validate(&json!({"slash": 1, "space": "x"})).is_ok());
+    assert!(schema.validate(&json!({"slash": "x"})).is_err());
+    assert!(schema.validate(&json!({"space": 1})).is_err());
+
+    assert!(ctx.get(&uri("http://example.com/schema.json#/properties/a~1b~0c")).is_some());
+}
+
+#[test]
+fn anchor_and_pointer_refs_in_one_document() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "definitions": {
+            "positive": {"$id": "#positive", "type": "integer", "minimum": 1},
+            "name": {"type": "string"}
+        },
+        "properties": {
+            "anchor": {"$ref": "#positive"},
+            "pointer": {"$ref": "#/definitions/name"},
+            "both": {"$ref": "#/definitions/positive"},
+            "root": {"$ref": "#"}
+        }
+    }));
+
+    assert!(schema.validate(&json!({"anchor": 1, "pointer": "x", "both": 2})).is_ok());
+    assert!(schema.validate(&json!({"anchor": 0})).is_err());
+    assert!(schema.validate(&json!({"pointer": 1})).is_err());
+    assert!(schema.validate(&json!({"both": "x"})).is_err());
+    assert!(schema.validate(&json!({"root": {"anchor": 1}})).is_ok());
+    assert!(schema.validate(&json!({"root": {"anchor": 0}})).is_err());
+    assert!(ctx.validate_references().is_ok());
+
+    assert!(ctx.get(&uri("http://example.com/schema.json#positive")).is_some());
+    assert!(ctx.get(&uri("http://example.com/schema.json#missing")).is_none());
+}
+
+#[test]
+fn ref_to_document_with_its_own_schema_keyword() {
+    let mut ctx = Context::default();
+    let a = uri("http://example.com/a.json");
+    let b = uri("http://example.com/b.json");
+    ctx.make_schema(a.clone(), &json!({
+        "$schema": "http://json-schema.org/draft-06/schema#",
+        "properties": {"b": {"$ref": "b.json"}}
+    })).expect("Invalid schema");
+    ctx.make_schema(b.clone(), &json!({
+        "$schema": "http://json-schema.org/draft-06/schema#",
+        "type": "integer"
+    })).expect("Invalid schema");
+
+    assert!(ctx.validate(&a, &json!({"b": 1})).is_ok());
+    assert!(ctx.validate(&a, &json!({"b": "x"})).is_err());
+    assert!(ctx.validate_references().is_ok());
+}
+
+#[test]
+fn ref_to_boolean_subschema() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "definitions": {"yes": true, "no": false},
+        "properties": {
+            "any": {"$ref": "#/definitions/yes"},
+            "none": {"$ref": "#/definitions/no"}
+        }
+    }));
+
+    for value in &[json!(null), json!(1), json!("x"), json!([]), json!({})] {
+        assert!(schema.validate(&json!({"any": value})).is_ok());
+        assert!(schema.validate(&json!({"none": value})).is_err());
+        assert!(schema.is_valid(&json!({"any": value})));
+        assert!(!schema.is_valid(&json!({"none": value})));
+    }
+
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({"definitions": {"no": false}, "$ref": "#/definitions/no"}));
+    assert!(schema.validate(&json!(1)).is_err());
+}
+
+#[test]
+fn get_by_anchor() {
+    let mut ctx = Context::default();
+    make_schema(&mut ctx, json!({
+        "definitions": {
+            "user": {
+                "$id": "#User",
+                "required": ["name"],
+                "properties": {"name": {"type": "string"}}
+            },
+            "group": {"$id": "#Group", "type": "array", "items": {"$ref": "#User"}}
+        }
+    }));
+    let other = uri("http://example.com/other.json");
+    ctx.make_schema(other.clone(), &json!({"$id": "#Other"}))
+        .expect("Invalid schema");
+
+    let base = base_uri();
+    let user = ctx.get_by_anchor(&base, "User").expect("Couldn't find anchor");
+    assert!(user.validate(&json!({"name": "Alice"})).is_ok());
+    assert!(user.validate(&json!({"name": 1})).is_err());
+    assert!(user.validate(&json!({})).is_err());
+
+    let group = ctx.get_by_anchor(&base, "Group").expect("Couldn't find anchor");
+    assert!(group.validate(&json!([{"name": "Alice"}])).is_ok());
+    assert!(group.validate(&json!([{}])).is_err());
+
+    assert!(ctx.get_by_anchor(&other, "User").is_none());
+    assert!(ctx.get_by_anchor(&base, "Other").is_none());
+    assert!(ctx.get_by_anchor(&other, "Other").is_some());
+    assert!(ctx.get_by_anchor(&base, "").is_none());
+    assert!(ctx.get_by_anchor(&base, "/definitions/user").is_none());
+}
+
+#[test]
+fn resolve_returns_the_referenced_schema() {
+    let mut ctx = Context::default();
+    make_schema(&mut ctx, json!({
+        "definitions": {
+            "name": {"type": ["string"], "minLength": 1, "x-unknown": true},
+            "positive": {"$id": "#positive", "type": "integer", "minimum": 1}
+        },
+        "properties": {"name": {"$ref": "#/definitions/name"}}
+    }));
+
+    assert_eq!(ctx.resolve(&uri("http://example.com/schema.json#/definitions/name")), Some(json!({"type": "string", "minLength": 1})));
+
+    assert_eq!(ctx.resolve(&uri("http://example.com/schema.json#/properties/name")), Some(json!({"$ref": "http://example.com/schema.json#/definitions/name"})));
+
+    assert_eq!(ctx.resolve(&uri("http://example.com/schema.json#positive")), Some(json!({"type": "integer", "minimum": 1})));
+
+    assert_eq!(ctx.resolve(&uri("http://example.com/schema.json#/definitions/missing")), None);
+}
+
+#[test]
+fn duplicate_anchors_conflict() {
+    let mut ctx = Context::default();
+    let base = base_uri();
+    match ctx.make_schema_unchecked(base.clone(), &json!({
+        "definitions": {
+            "a": {"$id": "#dup", "type": "integer"},
+            "b": {"properties": {"c": {"$id": "#dup", "type": "string"}}}
+        }
+    })) {
+        Err(FromValueError::URIConflict(_, ref uri)) => {
+            assert_eq!(uri.as_str(), "http://example.com/schema.json#dup");
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+
+    // The same anchor in different documents is fine, as is loading the
+    // same document again.
+    let doc = json!({"definitions": {"a": {"$id": "#dup"}}});
+    ctx.make_schema_unchecked(uri("http://example.com/a.json"), &doc)
+        .expect("Invalid schema");
+    ctx.make_schema_unchecked(uri("http://example.com/b.json"), &doc)
+        .expect("Invalid schema");
+    ctx.make_schema_unchecked(uri("http://example.com/b.json"), &doc)
+        .expect("Invalid schema");
+}
+
+#[test]
+fn failed_schema_leaves_no_anchors() {
+    let mut ctx = Context::default();
+    let base = base_uri();
+    assert!(ctx.make_schema_unchecked(base.clone(), &json!({
+        "definitions": {
+            "a": {"$id": "#a", "type": "integer"},
+            "b": {"minLength": -1}
+        }
+    })).is_err());
+    assert!(ctx.get_by_anchor(&base, "a").is_none());
+    assert!(ctx.get(&uri("http://example.com/schema.json#a")).is_none());
+}
+
+#[test]
+fn reloaded_schema_can_move_anchors() {
+    let mut ctx = Context::default();
+    let base = base_uri();
+    ctx.make_schema_unchecked(base.clone(), &json!({
+        "definitions": {
+            "a": {"$id": "#foo", "type": "integer"},
+            "b": {"type": "string"}
+        }
+    })).expect("Invalid schema");
+    ctx.make_schema_unchecked(base.clone(), &json!({
+        "definitions": {
+            "a": {"type": "integer"},
+            "b": {"$id": "#foo", "type": "string"}
+        }
+    })).expect("Anchor from the old version conflicted");
+
+    let foo = ctx.get_by_anchor(&base, "foo").expect("Anchor wasn't added");
+    assert_eq!(foo.id().as_str(), "http://example.com/schema.json#/definitions/b");
+    assert!(foo.is_valid(&json!("x")));
+
+    ctx.make_schema_unchecked(base.clone(), &json!({})).expect("Invalid schema");
+    assert!(ctx.get_by_anchor(&base, "foo").is_none());
+}
+
+#[test]
+fn annotations_of_referenced_schemas() {
+    let mut ctx = Context::default();
+    make_schema(&mut ctx, json!({
+        "title": "Root",
+        "properties": {
+            "user": {"$ref": "#/definitions/user", "title": "Owner"},
+            "group": {"$ref": "#Group"}
+        },
+        "definitions": {
+            "user": {"title": "User", "description": "Someone with an account."},
+            "group": {"$id": "#Group", "title": "Group"}
+        }
+    }));
+
+    let root = base_uri();
+    let schema = ctx.get(&root).expect("Schema wasn't added");
+    assert_eq!(schema.title(), Some("Root"));
+    assert_eq!(schema.description(), None);
+
+    let user = root.join("#/properties/user").expect("Couldn't parse URI");
+    let user = ctx.get(&user).expect("Subschema wasn't added");
+    assert_eq!(user.title(), Some("Owner"));
+
+    let target = root.join("#/definitions/user").expect("Couldn't parse URI");
+    let target = ctx.get(&target).expect("Subschema wasn't added");
+    assert_eq!(target.id().as_str(), "http://example.com/schema.json#/definitions/user");
+    assert_eq!(target.title(), Some("User"));
+    assert_eq!(target.description(), Some("Someone with an account."));
+
+    let group = ctx.get_by_anchor(&root, "Group").expect("Anchor wasn't added");
+    assert_eq!(group.title(), Some("Group"));
+}
+
+#[test]
+fn ref_into_array_keywords() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "allOf": [{"type": "object"}],
+        "anyOf": [
+            {"required": ["a"]},
+            {"required": ["b"]},
+            {"properties": {"x": {"type": "integer", "minimum": 0}}}
+        ],
+        "items": [{"type": "string"}, {"maxLength": 2}],
+        "properties": {
+            "first": {"$ref": "#/allOf/0"},
+            "x": {"$ref": "#/anyOf/2/properties/x"},
+            "short": {"$ref": "#/items/1"}
+        }
+    }));
+
+    assert!(schema.validate(&json!({"first": {}, "x": 1, "short": "ab"})).is_ok());
+    // `"a"` satisfies the root's `anyOf`, so only the referenced schemas fail.
+    assert!(schema.validate(&json!({"a": 1, "first": {}})).is_ok());
+    assert!(schema.validate(&json!({"a": 1, "first": []})).is_err());
+    assert!(schema.validate(&json!({"a": 1, "x": -1})).is_err());
+    assert!(schema.validate(&json!({"a": 1, "x": "1"})).is_err());
+    assert!(schema.validate(&json!({"a": 1, "short": "abc"})).is_err());
+    assert!(ctx.validate_references().is_ok());
+}
+
+#[test]
+fn ref_to_document_root() {
+    let tree = json!({"value": 1, "children": [
+        {"value": 2, "children": []},
+        {"value": 3, "children": [{"value": 4, "children": []}]}
+    ]});
+    let bad = json!({"value": 1, "children": [{"value": 2, "children": [{"value": "x"}]}]});
+
+    for root_ref in &["#", ""] {
+        let mut ctx = Context::default();
+        let schema = make_schema(&mut ctx, json!({
+            "properties": {
+                "value": {"type": "integer"},
+                "children": {"items": {"$ref": root_ref}}
+            }
+        }));
+        assert!(schema.validate(&tree).is_ok());
+        assert!(schema.validate(&bad).is_err());
+        assert!(schema.is_valid(&tree));
+        assert!(!schema.is_valid(&bad));
+    }
+
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "$id": "t",
+        "properties": {
+            "value": {"type": "integer"},
+            "children": {"items": {"$ref": "t"}}
+        }
+    }));
+    assert!(schema.validate(&tree).is_ok());
+    assert!(schema.validate(&bad).is_err());
+    assert!(ctx.validate_references().is_ok());
+}
+
+#[test]
+fn ref_cycles_are_errors() {
+    let mut ctx = Context::default();
+    let schema = make_schema(&mut ctx, json!({
+        "allOf": [{"$ref": "#"}],
+        "definitions": {
+            "a": {"anyOf": [{"$ref": "#/definitions/b"}]},
+            "b": {"not": {"$ref": "#/definitions/a"}}
+        }
+    }));
+    // The cycle is caught the second time `#` is followed for the value.
+    let cycle = ValidationError::ReferenceCycle(uri("http://example.com/schema.json#"));
+    assert_eq!(schema.validate(&json!(1)), Err(ValidationError::AllOfBranchFailed {
+        index: 0,
+        cause: Box::new(ValidationError::AllOfBranchFailed { index: 0, cause: Box::new(cycle) }),
+    }));
+    assert!(!schema.is_valid(&json!(1)));
+    assert_eq!(schema.validate_tree(&json!(1)).len(), 1);
+    assert!(!schema.validate_each(&json!(1), |err| {
+        assert!(err.to_string().contains("never descends"));
+        false
+    }));
+    assert!(schema.validate_annotated(&json!(1)).is_err());
+
+    // A cycle through `not` is an error, rather than a failure to invert.
+    let a = ctx.get(&uri("http://example.com/schema.json#/definitions/a")).expect("Subschema wasn't added");
+    assert!(a.validate(&json!(1)).is_err());
+}
@@ -0,0 +1,62 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::{base_uri, uri};
+use json_schema::{Context, ValidationError};
+
+#[test]
+fn validate_reader_yields_one_result_per_line() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "type": "object",
+        "required": ["level"]
+    })).expect("Invalid schema");
+
+    let input = br#"{"level": "info", "msg": "started"}
+{"msg": "no level"}
+
+{"level": "warn"
+["not", "an", "object"]
+{"level": "error"}
+"#;
+    let results = schema.validate_reader(&input[..]).collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 5);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+    match results[2] {
+        Err(ValidationError::InvalidJson(_)) => {},
+        ref result => panic!("Expected a parse error, got {:?}", result),
+    }
+    assert!(results[3].is_err());
+    assert!(results[4].is_ok());
+}
+
+#[test]
+fn validate_ndjson_numbers_lines() {
+    let mut ctx = Context::default();
+    ctx.make_schema(base_uri(), &json!({"type": "integer"}))
+        .expect("Invalid schema");
+
+    let input = b"1\n\"two\"\n\n{\n4\n";
+    let results = ctx.validate_ndjson(&base_uri(), &input[..]).collect::<Vec<_>>();
+    assert_eq!(results.iter().map(|&(line, _)| line).collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+    assert!(results[0].1.is_ok());
+    assert!(results[1].1.is_err());
+    match results[2].1 {
+        Err(ValidationError::InvalidJson(_)) => {},
+        ref result => panic!("Expected a parse error, got {:?}", result),
+    }
+    assert!(results[3].1.is_ok());
+
+    let missing = uri("http://example.com/missing.json");
+    let results = ctx.validate_ndjson(&missing, &b"1\n2\n"[..]).collect::<Vec<_>>();
+    assert_eq!(results, vec![
+        (1, Err(ValidationError::BadReference(missing.clone()))),
+        (2, Err(ValidationError::BadReference(missing))),
+    ]);
+}
@@ -7,7 +7,7 @@ extern crate url;
 
 use json_schema::{Context, JsonSchema};
 use serde_json::{from_reader, Value};
-use std::fs::{File, read_dir};
+use std::fs::{File, ReadDir, read_dir};
 use url::Url;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -25,11 +25,25 @@ struct TestCase {
 }
 
 const TEST_SUITE_DIR: &str = "tests/JSON-Schema-Test-Suite/tests/draft6";
+const FIXTURES_DIR: &str = "tests/fixtures";
 
 #[test]
 fn test_suite() {
-    let all_tests = read_dir(TEST_SUITE_DIR)
-        .expect("Couldn't find test suite -- did you run 'git submodule init; git submodule update'?")
+    let dir = read_dir(TEST_SUITE_DIR)
+        .expect("Couldn't find test suite -- did you run 'git submodule init; git submodule update'?");
+    test_dir(dir);
+}
+
+/// Runs our own test cases, which are in the same format as the test suite's.
+#[test]
+fn fixtures() {
+    let dir = read_dir(FIXTURES_DIR)
+        .expect("Couldn't find fixtures");
+    test_dir(dir);
+}
+
+fn test_dir(dir: ReadDir) {
+    let all_tests = dir
         .filter_map(|r| r.ok())
         .filter(|f| f.file_type()
             .map(|t| t.is_file())
@@ -46,8 +60,12 @@ fn test_suite() {
         let tests: Vec<Test> = from_reader(file)
             .expect("Couldn't read test cases");
         for (i, test) in tests.into_iter().enumerate() {
-            let uri = base_uri.join(&format!("#/{}/schema", i))
-                .expect("Couldn't create schema URI");
+            // Each schema is its own document, so that a `$ref` to `#/...`
+            // is resolved relative to the schema rather than the file.
+            let mut uri = base_uri.clone();
+            uri.path_segments_mut()
+                .expect("Couldn't create schema URI")
+                .push(&i.to_string());
             test_one(&mut ctx, uri, test);
         }
     }
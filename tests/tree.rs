@@ -0,0 +1,56 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{BoundKind, Condition, Context, Type, ValidationError};
+
+#[test]
+fn errors_are_arranged_by_path() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "required": ["name"],
+        "properties": {
+            "tags": {"items": {"type": "string", "minLength": 2}},
+            "a/b": {"type": "integer"}
+        }
+    })).expect("Invalid schema");
+
+    assert!(schema.validate_tree(&json!({"name": "x", "tags": ["ab"]})).is_empty());
+
+    let tree = schema.validate_tree(&json!({"tags": ["ab", 1, "c"], "a/b": "x"}));
+    assert_eq!(tree.len(), 4);
+    assert!(!tree.is_empty());
+    assert_eq!(tree.to_flat_vec(), vec![
+        ("".to_string(), ValidationError::ConditionFailed(Condition::Required(vec!["name".to_string()]))),
+        ("/a~1b".to_string(), ValidationError::ConditionFailed(Condition::Type(vec![Type::Integer]))),
+        ("/tags/1".to_string(), ValidationError::ConditionFailed(Condition::Type(vec![Type::String]))),
+        ("/tags/2".to_string(), ValidationError::ConditionFailed(Condition::MinLength(2))),
+    ]);
+}
+
+#[test]
+fn tree_iterates_over_pointers() {
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(base_uri(), &json!({
+        "allOf": [
+            {"properties": {"a": {"type": "string"}}},
+            {"properties": {"a": {"minimum": 1}}}
+        ]
+    })).expect("Invalid schema");
+
+    let value = json!({"a": 0});
+    let tree = schema.validate_tree(&value);
+    let mut errors = Vec::new();
+    for (path, err) in tree {
+        assert_eq!(path.get(&value).ok(), Some(&json!(0)));
+        errors.push(err);
+    }
+    assert_eq!(errors, vec![
+        ValidationError::ConditionFailed(Condition::Type(vec![Type::String])),
+        ValidationError::NumberOutOfRange { bound: 1.into(), actual: 0.into(), kind: BoundKind::Minimum },
+    ]);
+}
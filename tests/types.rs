@@ -0,0 +1,137 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+mod common;
+
+use common::base_uri;
+use json_schema::{is_well_formed, type_schema, Condition, Context, FromValueError, Type, ValidationError};
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const ALL_TYPES: [Type; 7] = [
+    Type::Null,
+    Type::Boolean,
+    Type::Number,
+    Type::Integer,
+    Type::String,
+    Type::Array,
+    Type::Object,
+];
+
+#[test]
+fn type_names_round_trip() {
+    for &t in ALL_TYPES.iter() {
+        assert_eq!(Type::from_string(t.as_str()), Some(t));
+        assert_eq!(t.to_string(), t.as_str());
+    }
+}
+
+fn hash_of<T: Hash>(t: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn types_and_conditions_hash() {
+    let types = vec![Type::Integer, Type::Integer].into_iter().collect::<HashSet<_>>();
+    assert_eq!(types.len(), 1);
+
+    let one = Condition::Const(json!({"a": [1, 2]}));
+    assert_eq!(hash_of(&one), hash_of(&Condition::Const(json!({"a": [1.0, 2]}))));
+    assert_ne!(hash_of(&one), hash_of(&Condition::Const(json!({"a": [2, 1]}))));
+    assert_eq!(hash_of(&Condition::Minimum(1.into())), hash_of(&Condition::Minimum(1.into())));
+    assert_ne!(hash_of(&Condition::Minimum(1.into())), hash_of(&Condition::Maximum(1.into())));
+}
+
+#[test]
+// Pattern conditions hold a Regex, which has a cache inside, but it isn't
+// part of the hash.
+#[allow(clippy::mutable_key_type)]
+fn conditions_in_hash_set() {
+    let conditions = vec![
+        Condition::Minimum(1.into()),
+        Condition::Minimum(1.into()),
+        Condition::Maximum(1.into()),
+        Condition::Const(json!({"a": [1, 2]})),
+        Condition::Const(json!({"a": [1, 2]})),
+        Condition::Const(json!({"a": [2, 1]})),
+        Condition::Enum(vec![json!(null), json!("x")]),
+        Condition::Type(vec![Type::Integer]),
+        Condition::Type(vec![Type::Integer]),
+        Condition::Required(vec!["a".to_string()]),
+        Condition::UniqueItems(true),
+    ];
+    let set = conditions.iter().cloned().collect::<HashSet<_>>();
+    assert_eq!(set.len(), 8);
+    for condition in &conditions {
+        assert!(set.contains(condition));
+    }
+    assert!(!set.contains(&Condition::Minimum(2.into())));
+}
+
+#[test]
+fn type_schemas_check_only_the_type() {
+    let number = type_schema(Type::Number);
+    assert!(number.validate(&json!(2.5)).is_ok());
+    assert!(number.validate(&json!(3)).is_ok());
+    assert_eq!(number.validate(&json!("x")),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::Number]))));
+    assert_eq!(number.to_value(), json!({"type": "number"}));
+
+    for &t in ALL_TYPES.iter() {
+        assert_eq!(type_schema(t).to_value(), json!({"type": t.as_str()}));
+    }
+    assert!(type_schema(Type::Null).is_valid(&json!(null)));
+    assert!(!type_schema(Type::Null).is_valid(&json!(0)));
+}
+
+#[test]
+fn empty_type_array_is_rejected() {
+    match is_well_formed(&json!({"type": []})) {
+        Err(FromValueError::InvalidKeywordValue(_, ref keyword, ref value)) => {
+            assert_eq!(keyword, "type");
+            assert_eq!(value, &json!([]));
+        },
+        r => panic!("Wrong result: {:?}", r),
+    }
+    assert_eq!(is_well_formed(&json!({"type": ["null"]})), Ok(()));
+}
+
+#[test]
+fn repeated_types_are_dropped() {
+    let uri = base_uri();
+    let schema = json!({"type": ["string", "string", "number", "string"]});
+
+    let mut ctx = Context::default();
+    let parsed = ctx.make_schema_unchecked(uri.clone(), &schema).expect("Invalid schema");
+    assert_eq!(parsed.to_value(), json!({"type": ["string", "number"]}));
+    assert_eq!(parsed.validate(&json!(null)),
+        Err(ValidationError::ConditionFailed(Condition::Type(vec![Type::String, Type::Number]))));
+
+    let mut ctx = Context::default();
+    ctx.set_strict_keywords(true);
+    match ctx.make_schema_unchecked(uri, &schema) {
+        Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "type"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+}
+
+#[test]
+fn empty_schema_accepts_everything() {
+    let uri = base_uri();
+    let mut ctx = Context::default();
+    let schema = ctx.make_schema(uri, &json!({})).expect("Invalid schema");
+
+    let values = [json!(null), json!(true), json!(2.5), json!(3), json!("x"), json!([1]), json!({"a": 1})];
+    for value in values.iter() {
+        assert_eq!(schema.validate(value), Ok(()));
+        assert!(schema.is_valid(value));
+        assert!(schema.validate_tree(value).is_empty());
+    }
+    assert_eq!(schema.to_value(), json!({}));
+    assert!(schema.lint().is_empty());
+}
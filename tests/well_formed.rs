@@ -0,0 +1,48 @@
+extern crate json_schema;
+#[macro_use]
+extern crate serde_json;
+
+use json_schema::{is_well_formed, FromValueError};
+
+#[test]
+fn well_formed_schema() {
+    assert_eq!(is_well_formed(&json!(true)), Ok(()));
+    assert_eq!(is_well_formed(&json!({
+        "$id": "person.json",
+        "type": "object",
+        "properties": {
+            "name": {"type": "string", "maxLength": 100},
+            "friends": {"type": "array", "items": {"$ref": "#"}}
+        },
+        "required": ["name"],
+        "additionalProperties": {"$ref": "elsewhere.json"}
+    })), Ok(()));
+}
+
+#[test]
+fn malformed_schemas() {
+    match is_well_formed(&json!({"maximum": "10"})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "maximum"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match is_well_formed(&json!({"required": ["a", 1]})) {
+        Err(FromValueError::InvalidKeywordType(_, ref keyword, _)) => assert_eq!(keyword, "required"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match is_well_formed(&json!({"properties": {"a": {"minLength": -1}}})) {
+        Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "minLength"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match is_well_formed(&json!({"type": "integr"})) {
+        Err(FromValueError::InvalidKeywordValue(_, ref keyword, _)) => assert_eq!(keyword, "type"),
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match is_well_formed(&json!({"pattern": "("})) {
+        Err(FromValueError::BadPattern(..)) => {},
+        r => panic!("Wrong result: {:?}", r),
+    }
+    match is_well_formed(&json!([])) {
+        Err(FromValueError::InvalidSchemaType(_)) => {},
+        r => panic!("Wrong result: {:?}", r),
+    }
+}